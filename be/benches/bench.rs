@@ -2,7 +2,7 @@
 extern crate criterion;
 use criterion::Criterion;
 
-use be::{Board, Bot, Game, Level};
+use be::{Board, Bot, Engine, Game, Level};
 
 fn init_game(cells: &[i8], cells_to_win: u8) -> Game {
     let board = Board {
@@ -11,6 +11,7 @@ fn init_game(cells: &[i8], cells_to_win: u8) -> Game {
         p1_mark: -1,
         bot_mark: 1,
         empty_mark: 0,
+        ..Default::default()
     };
     match Game::new(board, Level::Normal) {
         Ok(game) => game,
@@ -18,6 +19,12 @@ fn init_game(cells: &[i8], cells_to_win: u8) -> Game {
     }
 }
 
+fn init_mcts_game(cells: &[i8], cells_to_win: u8) -> Game {
+    let mut game = init_game(cells, cells_to_win);
+    game.engine = Engine::Mcts;
+    game
+}
+
 fn bench_bot_player(c: &mut Criterion) {
     let mut group = c.benchmark_group("bot::next_move");
     group.sample_size(10);
@@ -91,6 +98,36 @@ fn bench_bot_player(c: &mut Criterion) {
         })
     });
 
+    group.bench_function("5x5 10th move mcts", |b| {
+        b.iter(|| {
+            Bot::next_move(init_mcts_game(
+                &[
+                    0, 0, 0, 0, -1, 0, 1, -1, 0, 0, 0, -1, 1, 1, 0, 0, -1, 1, -1, 0, 0, 0, 0, 0, 0,
+                ],
+                5,
+            ))
+        })
+    });
+
+    group.bench_function("15x15 40th move mcts", |b| {
+        b.iter(|| {
+            Bot::next_move(init_mcts_game(
+                &[
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                    -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1,
+                    0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, -1, 0, 0, 0, 0, 0, 0, 0,
+                    0, 0, 0, 0, 0, 1, 0, -1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1, 1, -1, 0, 0,
+                    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, -1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, -1, -1, -1,
+                    -1, 1, 0, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1, 1, -1, -1, 1, 1, 0, 0, 0, 0, 0, 0,
+                    0, 0, 1, -1, 0, 1, 1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1, 0, 0, 0, 0, -1, 0,
+                    0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0,
+                ],
+                5,
+            ))
+        })
+    });
+
     group.finish();
 }
 
@@ -1,12 +1,16 @@
 use actix_cors::Cors;
 use actix_governor::{Governor, GovernorConfigBuilder};
 use actix_web::{guard, http::header, web, App, HttpServer};
-use std::{env, io};
+use std::{cmp, env, fs, io, thread};
 
+#[path = "../bitboard.rs"]
+mod bitboard;
 #[path = "../bot.rs"]
 mod bot;
 #[path = "../conf.rs"]
 mod conf;
+#[path = "../evaluation.rs"]
+mod evaluation;
 #[path = "../first_move.rs"]
 mod first_move;
 #[path = "../game.rs"]
@@ -15,15 +19,28 @@ mod game;
 mod guards;
 #[path = "../handlers.rs"]
 mod handlers;
+#[path = "../mcts.rs"]
+mod mcts;
 #[path = "../models.rs"]
 mod models;
+#[path = "../slots.rs"]
+mod slots;
+#[path = "../threats.rs"]
+mod threats;
+#[path = "../transposition.rs"]
+mod transposition;
 
+use conf::BoardTable;
 use guards::RefererGuard;
-use handlers::{hello, next_move, robots_txt};
+use handlers::{analyze, hello, hint, next_move, opening, robots_txt};
+use slots::{SearchConf, SearchSlots};
 
 const ALLOWED_DEV_CLIENT_URL: &str = "http://localhost:5173";
 const ALLOWED_DEV_CLIENT_URL_2: &str = "http://127.0.0.1:5173";
 
+// Threads per search unless set otherwise, if there are as many cores available
+const DEFAULT_SEARCH_THREADS: usize = 4;
+
 #[actix_web::main]
 async fn main() -> io::Result<()> {
     let port = match env::var("PORT") {
@@ -38,6 +55,31 @@ async fn main() -> io::Result<()> {
 
     let client_url = env::var("CLIENT_URL").ok();
 
+    let available_cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+
+    // Threads per search and concurrently running searches, requests exceeding the
+    // latter get 503 responses. Defaults share the cores between the searches.
+    let (search_threads, max_searches) = match (
+        env_var_positive("SEARCH_THREADS"),
+        env_var_positive("MAX_SEARCHES"),
+    ) {
+        (Some(threads), Some(searches)) => (threads, searches),
+        (Some(threads), None) => (threads, cmp::max(available_cores / threads, 1)),
+        (None, Some(searches)) => (cmp::max(available_cores / searches, 1), searches),
+        (None, None) => {
+            let threads = cmp::min(available_cores, DEFAULT_SEARCH_THREADS);
+            (threads, available_cores / threads)
+        }
+    };
+
+    let search_conf = SearchConf {
+        threads: search_threads,
+        max_searches,
+    };
+    let search_slots = web::Data::new(SearchSlots::new(search_conf.max_searches));
+
+    let board_table = web::Data::new(board_table()?);
+
     let allowed_referers = if let Some(client_url) = &client_url {
         vec![client_url.clone()]
     } else {
@@ -51,6 +93,9 @@ async fn main() -> io::Result<()> {
 
     HttpServer::new(move || {
         App::new()
+            .app_data(web::Data::new(search_conf))
+            .app_data(search_slots.clone())
+            .app_data(board_table.clone())
             .wrap(
                 if let Some(client_url) = &client_url {
                     Cors::default().allowed_origin(client_url)
@@ -73,6 +118,27 @@ async fn main() -> io::Result<()> {
                             .guard(guard::Header("content-type", "application/json"))
                             .to(next_move),
                     )
+                    .route(
+                        "/bot/opening",
+                        web::post()
+                            .guard(RefererGuard::new(allowed_referers.clone()))
+                            .guard(guard::Header("content-type", "application/json"))
+                            .to(opening),
+                    )
+                    .route(
+                        "/hint",
+                        web::post()
+                            .guard(RefererGuard::new(allowed_referers.clone()))
+                            .guard(guard::Header("content-type", "application/json"))
+                            .to(hint),
+                    )
+                    .route(
+                        "/analyze",
+                        web::post()
+                            .guard(RefererGuard::new(allowed_referers.clone()))
+                            .guard(guard::Header("content-type", "application/json"))
+                            .to(analyze),
+                    )
                     .route(
                         "/hello",
                         web::get()
@@ -86,3 +152,26 @@ async fn main() -> io::Result<()> {
     .run()
     .await
 }
+
+fn env_var_positive(key: &str) -> Option<usize> {
+    env::var(key)
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|&value| value > 0)
+}
+
+/// Board table given as JSON in env var BOARD_TABLE or in file BOARD_TABLE_FILE.
+fn board_table() -> io::Result<BoardTable> {
+    let json = match (env::var("BOARD_TABLE"), env::var("BOARD_TABLE_FILE")) {
+        (Ok(json), _) => json,
+        (_, Ok(path)) => fs::read_to_string(path)?,
+        _ => return Ok(BoardTable::default()),
+    };
+
+    BoardTable::from_json(&json).map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Board table is invalid: {:?}", error),
+        )
+    })
+}
@@ -0,0 +1,456 @@
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use crate::models::Topology;
+
+const WORDS: usize = 6;
+const WORD_BITS: usize = 64;
+
+/// Row and column steps of the line directions, in the order of `LineWindows::all`.
+pub const LINE_DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+// Enough bits for counting the markers of a window up to MAX_WINDOW_CELLS long
+const COUNTER_PLANES: usize = 4;
+
+/// Longest line window whose markers the window counters can count.
+pub const MAX_WINDOW_CELLS: usize = (1 << COUNTER_PLANES) - 1;
+
+/// Fixed size bitset with one bit per board cell.
+///
+/// Bit `i` corresponds to the board index `i`, i.e. bits 0-(n-1) represent
+/// the first row of an m x n board, n-(2n-1) the second row and etc.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bitboard([u64; WORDS]);
+
+impl Bitboard {
+    pub fn from_indices(indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut bitboard = Self::default();
+        for idx in indices {
+            bitboard.set(idx);
+        }
+        bitboard
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.0[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.0[index / WORD_BITS] &= !(1 << (index % WORD_BITS));
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.0[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    pub fn count_ones(&self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    /// Indices of the set bits in increasing order.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &word)| {
+            (0..WORD_BITS)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * WORD_BITS + bit)
+        })
+    }
+
+    /// Shift towards lower indices, moving bit `index + n` to `index`.
+    pub fn shr(&self, n: usize) -> Self {
+        let (word_shift, bit_shift) = (n / WORD_BITS, n % WORD_BITS);
+        let mut shifted = [0; WORDS];
+
+        if bit_shift == 0 {
+            shifted[..WORDS - word_shift].copy_from_slice(&self.0[word_shift..]);
+            return Bitboard(shifted);
+        }
+
+        for (i, word) in shifted.iter_mut().enumerate().take(WORDS - word_shift) {
+            let src = i + word_shift;
+            *word = self.0[src] >> bit_shift;
+            if src + 1 < WORDS {
+                *word |= self.0[src + 1] << (WORD_BITS - bit_shift);
+            }
+        }
+        Bitboard(shifted)
+    }
+
+    /// Shift towards higher indices, moving bit `index` to `index + n`.
+    pub fn shl(&self, n: usize) -> Self {
+        let (word_shift, bit_shift) = (n / WORD_BITS, n % WORD_BITS);
+        let mut shifted = [0; WORDS];
+
+        if bit_shift == 0 {
+            shifted[word_shift..].copy_from_slice(&self.0[..WORDS - word_shift]);
+            return Bitboard(shifted);
+        }
+
+        for (i, word) in shifted.iter_mut().enumerate().skip(word_shift) {
+            let src = i - word_shift;
+            *word = self.0[src] << bit_shift;
+            if src > 0 {
+                *word |= self.0[src - 1] >> (WORD_BITS - bit_shift);
+            }
+        }
+        Bitboard(shifted)
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Bitboard(std::array::from_fn(|i| self.0[i] & rhs.0[i]))
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        Bitboard(std::array::from_fn(|i| self.0[i] ^ rhs.0[i]))
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Bitboard(std::array::from_fn(|i| self.0[i] | rhs.0[i]))
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Bitboard(self.0.map(|word| !word))
+    }
+}
+
+/// Windows of `cells_to_win` consecutive cells in one line direction.
+///
+/// Window starting from cell `i` consists of cells `i + j * step` for
+/// `j` in 0..`cells_to_win`. Bits of `starts` mark the cells from which
+/// a window fits to the board without wrapping over a board edge.
+///
+/// On a torus the lines wrap over the board edges, so that every cell
+/// starts a window and the window cells are taken modulo the board
+/// dimensions instead.
+///
+/// Bits of `before` and `after` mark the windows having a flanking cell,
+/// i.e. a cell right before the first or after the last cell of the window.
+#[derive(Clone, Copy, Debug)]
+pub struct LineWindows {
+    pub step: usize,
+    pub starts: Bitboard,
+    pub before: Bitboard,
+    pub after: Bitboard,
+    d_row: isize,
+    d_col: isize,
+    rows: usize,
+    cols: usize,
+    torus: bool,
+}
+
+impl LineWindows {
+    /// Windows of rows, columns, diagonals and antidiagonals, in this order.
+    pub fn all(rows: usize, cols: usize, cells_to_win: usize, topology: Topology) -> [Self; 4] {
+        let torus = topology == Topology::Torus;
+
+        LINE_DIRECTIONS.map(|(d_row, d_col)| {
+            // Cells from which moving `steps` steps along the line stays on the board
+            let cells_with_room = |steps: isize| {
+                Bitboard::from_indices((0..rows * cols).filter(|idx| {
+                    let row = (idx / cols) as isize + steps * d_row;
+                    let col = (idx % cols) as isize + steps * d_col;
+                    torus || row >= 0 && row < rows as isize && col >= 0 && col < cols as isize
+                }))
+            };
+            let starts = cells_with_room(cells_to_win as isize - 1);
+
+            let mut windows = LineWindows {
+                step: (d_row * cols as isize + d_col) as usize,
+                starts,
+                before: starts & cells_with_room(-1),
+                after: starts & cells_with_room(cells_to_win as isize),
+                d_row,
+                d_col,
+                rows,
+                cols,
+                torus,
+            };
+
+            // Windows covering a whole line of the torus have no flanking cells
+            let covers_line =
+                (0..cells_to_win as isize).any(|j| windows.cell_at(0, j) == windows.cell_at(0, -1));
+            if torus && covers_line {
+                windows.before = Bitboard::default();
+                windows.after = Bitboard::default();
+            }
+            windows
+        })
+    }
+
+    /// Index of the cell `j` steps from cell `start` along the line.
+    fn cell_at(&self, start: usize, j: isize) -> usize {
+        if !self.torus {
+            return (start as isize + j * self.step as isize) as usize;
+        }
+        let row = ((start / self.cols) as isize + j * self.d_row).rem_euclid(self.rows as isize);
+        let col = ((start % self.cols) as isize + j * self.d_col).rem_euclid(self.cols as isize);
+
+        row as usize * self.cols + col as usize
+    }
+
+    /// Bits moved `j` steps backwards along the line, i.e. such that the bit of the
+    /// cell `j` steps from a cell ends up to that cell.
+    fn ahead(&self, bits: Bitboard, j: isize) -> Bitboard {
+        if !self.torus {
+            let shift = j.unsigned_abs() * self.step;
+            return if j >= 0 {
+                bits.shr(shift)
+            } else {
+                bits.shl(shift)
+            };
+        }
+
+        let (rows, cols) = (self.rows, self.cols);
+        let board = Bitboard::from_indices(0..rows * cols);
+        let d_row = (j * self.d_row).rem_euclid(rows as isize) as usize;
+        let d_col = (j * self.d_col).rem_euclid(cols as isize) as usize;
+
+        // Rotate the rows first and then the cells within every row
+        let bits = if d_row == 0 {
+            bits
+        } else {
+            (bits.shr(d_row * cols) | bits.shl((rows - d_row) * cols)) & board
+        };
+        if d_col == 0 {
+            return bits;
+        }
+        let unwrapped =
+            Bitboard::from_indices((0..rows * cols).filter(|idx| idx % cols < cols - d_col));
+
+        (bits.shr(d_col) & unwrapped) | (bits.shl(cols - d_col) & board & !unwrapped)
+    }
+
+    /// Remove the windows containing any of the cells in `bits`.
+    pub fn exclude(&mut self, bits: Bitboard, cells_to_win: usize) {
+        let touched = (0..cells_to_win as isize).fold(Bitboard::default(), |touched, j| {
+            touched | self.ahead(bits, j)
+        });
+
+        self.starts = self.starts & !touched;
+        self.before = self.before & self.starts;
+        self.after = self.after & self.starts;
+    }
+
+    /// Cell indices of every window of this line direction.
+    pub fn window_cells(&self, cells_to_win: usize) -> impl Iterator<Item = Vec<usize>> + '_ {
+        self.starts.indices().map(move |start| {
+            (0..cells_to_win as isize)
+                .map(|j| self.cell_at(start, j))
+                .collect()
+        })
+    }
+
+    /// Start cells of the windows fully occupied by `bits`.
+    pub fn full_windows(&self, bits: Bitboard, cells_to_win: usize) -> Bitboard {
+        (1..cells_to_win as isize).fold(bits & self.starts, |full, j| full & self.ahead(bits, j))
+    }
+
+    /// Start cells of the windows whose flanking cells before and after the
+    /// window are in `bits`, in this order.
+    pub fn flanked_windows(&self, bits: Bitboard, cells_to_win: usize) -> [Bitboard; 2] {
+        [
+            self.before & self.ahead(bits, -1),
+            self.after & self.ahead(bits, cells_to_win as isize),
+        ]
+    }
+
+    /// Flanking cells of every window of this line direction, in the order of `window_cells`.
+    pub fn window_flanks(&self, cells_to_win: usize) -> impl Iterator<Item = Vec<usize>> + '_ {
+        self.starts.indices().map(move |start| {
+            let mut flanks = Vec::with_capacity(2);
+            if self.before.contains(start) {
+                flanks.push(self.cell_at(start, -1));
+            }
+            if self.after.contains(start) {
+                flanks.push(self.cell_at(start, cells_to_win as isize));
+            }
+            flanks
+        })
+    }
+
+    /// Count the markers of both players in every window of this line direction.
+    pub fn window_counts(&self, player_bits: [Bitboard; 2], cells_to_win: usize) -> WindowCounts {
+        let mut planes = [[Bitboard::default(); COUNTER_PLANES]; 2];
+        let mut present = [Bitboard::default(); 2];
+
+        for j in 0..cells_to_win as isize {
+            for (p, bits) in player_bits.iter().enumerate() {
+                let shifted = self.ahead(*bits, j);
+                present[p] = present[p] | shifted;
+
+                // Bit-sliced addition of the shifted cells to the window counters
+                let mut carry = shifted;
+                for plane in planes[p].iter_mut() {
+                    let sum = *plane ^ carry;
+                    carry = *plane & carry;
+                    *plane = sum;
+                }
+            }
+        }
+
+        WindowCounts {
+            planes,
+            open_starts: [self.starts & !present[1], self.starts & !present[0]],
+        }
+    }
+}
+
+/// Bit-sliced marker counts of the windows of one line direction.
+pub struct WindowCounts {
+    planes: [[Bitboard; COUNTER_PLANES]; 2],
+    // Windows without the other player's markers
+    open_starts: [Bitboard; 2],
+}
+
+impl WindowCounts {
+    /// Start cells of the windows having exactly `count` cells of player `p`
+    /// (index to the player bitboards) and no cells of the other player.
+    pub fn open_windows(&self, p: usize, count: usize) -> Bitboard {
+        self.planes[p]
+            .iter()
+            .enumerate()
+            .fold(self.open_starts[p], |acc, (bit, &plane)| {
+                if count & (1 << bit) != 0 {
+                    acc & plane
+                } else {
+                    acc & !plane
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_over_word_boundaries() {
+        let bitboard = Bitboard::from_indices([0, 63, 64, 130, 224]);
+
+        assert_eq!(bitboard.shr(1), Bitboard::from_indices([62, 63, 129, 223]));
+        assert_eq!(bitboard.shr(64), Bitboard::from_indices([0, 66, 160]));
+        assert_eq!(bitboard.shr(100), Bitboard::from_indices([30, 124]));
+        assert_eq!(
+            bitboard.shl(1),
+            Bitboard::from_indices([1, 64, 65, 131, 225])
+        );
+        assert_eq!(
+            bitboard.shl(64),
+            Bitboard::from_indices([64, 127, 128, 194, 288])
+        );
+        assert_eq!(bitboard.count_ones(), 5);
+        assert_eq!(
+            bitboard.indices().collect::<Vec<usize>>(),
+            vec![0, 63, 64, 130, 224]
+        );
+    }
+
+    #[test]
+    fn window_starts_5x5() {
+        let [rows, cols, diags, antidiags] = LineWindows::all(5, 5, 4, Topology::Plane);
+
+        assert_eq!(rows.starts.count_ones(), 10);
+        assert_eq!(cols.starts.count_ones(), 10);
+        assert_eq!(diags.starts, Bitboard::from_indices([0, 1, 5, 6]));
+        assert_eq!(antidiags.starts, Bitboard::from_indices([3, 4, 8, 9]));
+        assert_eq!(antidiags.window_cells(4).next(), Some(vec![3, 7, 11, 15]));
+    }
+
+    #[test]
+    fn window_starts_3x4() {
+        let [rows, cols, diags, antidiags] = LineWindows::all(3, 4, 3, Topology::Plane);
+
+        assert_eq!(rows.starts, Bitboard::from_indices([0, 1, 4, 5, 8, 9]));
+        assert_eq!(cols.starts, Bitboard::from_indices([0, 1, 2, 3]));
+        assert_eq!(diags.starts, Bitboard::from_indices([0, 1]));
+        assert_eq!(antidiags.starts, Bitboard::from_indices([2, 3]));
+        assert_eq!(antidiags.window_cells(3).next(), Some(vec![2, 5, 8]));
+        assert_eq!(rows.before, Bitboard::from_indices([1, 5, 9]));
+        assert_eq!(rows.after, Bitboard::from_indices([0, 4, 8]));
+        assert!(cols.before.is_empty());
+        assert_eq!(
+            rows.window_flanks(3).collect::<Vec<Vec<usize>>>(),
+            vec![vec![3], vec![0], vec![7], vec![4], vec![11], vec![8]]
+        );
+    }
+
+    #[test]
+    fn window_cells_torus_4x4() {
+        let [rows, cols, diags, antidiags] = LineWindows::all(4, 4, 3, Topology::Torus);
+
+        assert_eq!(rows.starts.count_ones(), 16);
+        assert_eq!(rows.window_cells(3).nth(3), Some(vec![3, 0, 1]));
+        assert_eq!(cols.window_cells(3).nth(12), Some(vec![12, 0, 4]));
+        assert_eq!(diags.window_cells(3).nth(15), Some(vec![15, 0, 5]));
+        assert_eq!(antidiags.window_cells(3).next(), Some(vec![0, 7, 10]));
+        assert_eq!(rows.window_flanks(3).next(), Some(vec![3, 3]));
+
+        let bits = Bitboard::from_indices([3, 0, 1, 13]);
+        assert_eq!(rows.full_windows(bits, 3), Bitboard::from_indices([3]));
+        assert_eq!(cols.full_windows(bits, 3), Bitboard::default());
+
+        // Windows covering a whole row have no flanking cells
+        let [rows, ..] = LineWindows::all(4, 4, 4, Topology::Torus);
+        assert!(rows.before.is_empty() && rows.after.is_empty());
+    }
+
+    #[test]
+    fn exclude_windows_of_cells_5x5() {
+        let [mut rows, _, mut diags, _] = LineWindows::all(5, 5, 4, Topology::Plane);
+        rows.exclude(Bitboard::from_indices([7]), 4);
+        diags.exclude(Bitboard::from_indices([7]), 4);
+
+        assert_eq!(rows.starts.count_ones(), 8);
+        assert!(!rows.starts.contains(5) && !rows.starts.contains(6));
+        assert_eq!(rows.before, Bitboard::from_indices([1, 11, 16, 21]));
+        assert_eq!(diags.starts, Bitboard::from_indices([0, 5, 6]));
+    }
+
+    #[test]
+    fn full_windows_do_not_wrap() {
+        let [rows, ..] = LineWindows::all(5, 5, 4, Topology::Plane);
+        // Cells 3-6 are consecutive indices but on different rows
+        let bits = Bitboard::from_indices([3, 4, 5, 6, 10, 11, 12, 13]);
+
+        assert_eq!(rows.full_windows(bits, 4), Bitboard::from_indices([10]));
+    }
+
+    #[test]
+    fn window_counts_3x3() {
+        let [rows, cols, ..] = LineWindows::all(3, 3, 3, Topology::Plane);
+        let player_bits = [
+            Bitboard::from_indices([8]),
+            Bitboard::from_indices([0, 1, 4]),
+        ];
+
+        let row_counts = rows.window_counts(player_bits, 3);
+        assert_eq!(row_counts.open_windows(1, 2), Bitboard::from_indices([0]));
+        assert_eq!(row_counts.open_windows(1, 1), Bitboard::from_indices([3]));
+        assert!(row_counts.open_windows(1, 0).is_empty());
+        assert_eq!(row_counts.open_windows(0, 1), Bitboard::from_indices([6]));
+
+        let col_counts = cols.window_counts(player_bits, 3);
+        assert_eq!(col_counts.open_windows(1, 1), Bitboard::from_indices([0]));
+        assert_eq!(col_counts.open_windows(1, 2), Bitboard::from_indices([1]));
+        assert!(col_counts.open_windows(1, 3).is_empty());
+    }
+}
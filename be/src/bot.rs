@@ -1,15 +1,34 @@
-use rand::{seq::SliceRandom, Rng};
-use std::cmp;
+use rand::seq::SliceRandom;
+use std::{
+    cmp::{self, Reverse},
+    slice,
+    sync::atomic::{AtomicI32, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    conf::BoardSize,
     first_move::FirstMove,
     game::Game,
-    models::{BotMove, Level},
+    mcts::Mcts,
+    models::{
+        Analysis, BotMove, Engine, Level, OpeningChoice, OpeningMove, OpeningRule, SearchStats,
+        Topology,
+    },
+    threats::{ThreatMode, ThreatSearch},
+    transposition::{Bound, TranspositionTable, TtEntry},
 };
 
 const EASY_LEVEL_RANDOM_MOVE_THRESHOLD: f32 = 0.67;
 
+// Threat space search is used for boards with at least this many cells
+const THREAT_SPACE_MIN_CELLS: usize = 100;
+
+// Positions valued at most this far from zero are balanced enough for swap2's two extra stones
+const SWAP2_BALANCED_MAX_VALUE: i32 = 600;
+// Candidate cells per colour for the two extra stones of swap2
+const SWAP2_CANDIDATE_CELLS: usize = 8;
+
 /// Type to represent the computer player, aka bot.
 ///
 /// Use method `next_move` to play one round of a game.
@@ -27,6 +46,7 @@ const EASY_LEVEL_RANDOM_MOVE_THRESHOLD: f32 = 0.67;
 ///     p1_mark: 1,
 ///     bot_mark: -1,
 ///     empty_mark: 0,
+///     ..Default::default()
 /// };
 ///
 /// let game = match Game::new(board, Level::Normal) {
@@ -43,16 +63,101 @@ const EASY_LEVEL_RANDOM_MOVE_THRESHOLD: f32 = 0.67;
 /// ```
 pub struct Bot;
 
+/// State shared by the recursive minimax calls of one `next_move` call.
+#[derive(Default)]
+struct Search {
+    tt: TranspositionTable,
+    nodes: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+}
+
+/// Best root move found by minimax, its value and the depth searched.
+#[derive(Clone, Copy, Debug)]
+struct RootMove {
+    cell: usize,
+    value: i32,
+    depth: i32,
+}
+
 impl Bot {
-    pub fn next_move(mut game: Game) -> BotMove {
-        let cells_count = game.cells.len();
-        let empty_cells = game.empty_cell_count();
+    pub fn next_move(game: Game) -> BotMove {
+        let started = Instant::now();
+        let verbose = game.verbose;
+        let gravity_cols = game.gravity.then(|| game.cols());
+        let mut stats = SearchStats::default();
+
+        let mut bot_move = if game.connect6 {
+            Self::search_connect6_turn(game, &mut stats)
+        } else {
+            Self::search_next_move(game, &mut stats)
+        };
+
+        if let (Some(cols), true) = (gravity_cols, bot_move.next_is_valid) {
+            bot_move.column = Some((bot_move.next as usize % cols) as u8);
+        }
+
+        if verbose {
+            if bot_move.next_is_valid && stats.principal_variation.is_empty() {
+                stats.principal_variation.push(bot_move.next);
+            }
+            stats.elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+            bot_move.stats = Some(stats);
+        }
+        bot_move
+    }
 
-        if empty_cells == cells_count {
-            return Self::play_game_first_move(game, cells_count);
+    /// Both moves of the bot's Connect6 turn, the second one searched after the first.
+    ///
+    /// Both searches share the time budget of the turn, the first one gets half
+    /// of it and the second one what is left.
+    fn search_connect6_turn(mut game: Game, stats: &mut SearchStats) -> BotMove {
+        let deadline = game
+            .time_budget
+            .map(|time_budget| Instant::now() + time_budget);
+
+        let mut first_game = game.clone();
+        first_game.time_budget = game.time_budget.map(|time_budget| time_budget / 2);
+        let mut bot_move = Self::search_next_move(first_game, stats);
+
+        if !bot_move.next_is_valid {
+            return bot_move;
         }
+        bot_move.moves.push(bot_move.next);
 
-        if empty_cells == cells_count - 1 {
+        game.make_move(bot_move.next as usize, game.bot_mark);
+
+        // The first move of the game is a turn of its own
+        if bot_move.game_over || game.side_to_move() != game.bot_mark {
+            return bot_move;
+        }
+
+        game.time_budget =
+            deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        let mut second_stats = SearchStats::default();
+        let second_move = Self::search_next_move(game, &mut second_stats);
+        stats.nodes += second_stats.nodes;
+
+        if second_move.next_is_valid {
+            bot_move.moves.push(second_move.next);
+        }
+        bot_move.captured.extend(second_move.captured);
+        bot_move.game_over = second_move.game_over;
+        bot_move.winner = second_move.winner;
+
+        bot_move
+    }
+
+    fn search_next_move(mut game: Game, stats: &mut SearchStats) -> BotMove {
+        // Blocked cells don't count as markers
+        let markers = game.marker_count();
+
+        if markers == 0 {
+            return Self::play_game_first_move(game);
+        }
+
+        if markers == 1 {
             if let Some(bot_move) = Self::play_bot_first_move_if_defined(&game) {
                 return bot_move;
             }
@@ -64,35 +169,516 @@ impl Bot {
             }
         }
 
-        let init_depth = cmp::min(empty_cells, game.max_depth);
-        let first_player = game.bot_mark;
+        // Threat-space search takes its time from the same budget as the search after it
+        let deadline = game
+            .time_budget
+            .map(|time_budget| Instant::now() + time_budget);
 
-        let (_, best_move) = Self::minimax(
-            &mut game,
-            first_player,
-            init_depth as i32,
-            i32::MIN,
-            i32::MAX,
-            true,
-        );
+        if let Some(threat_move) = Self::threat_space_move(&mut game, deadline) {
+            return Self::complete_bot_move(game, Some(threat_move));
+        }
+        game.time_budget =
+            deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        let init_depth = cmp::min(game.empty_cell_count(), game.max_depth);
+
+        let best_move = match (game.engine, game.time_budget) {
+            (Engine::Mcts, time_budget) => {
+                let iterations = game.mcts_iterations;
+                Mcts::best_move(&mut game, iterations, time_budget)
+            }
+            (Engine::Minimax, time_budget) => {
+                let mut searches = Self::thread_searches(&game);
+                let root_move = match time_budget {
+                    Some(time_budget) => {
+                        Self::iterative_deepening(&mut game, &mut searches, init_depth, time_budget)
+                    }
+                    None => Self::root_search(&mut game, &mut searches, init_depth as i32, None),
+                };
+
+                Self::record_search_stats(&mut game, &searches, root_move, stats);
+                root_move.map(|root_move| root_move.cell)
+            }
+        };
 
         Self::complete_bot_move(game, best_move)
     }
 
-    fn play_game_first_move(game: Game, cells_count: usize) -> BotMove {
+    /// Score every playable move of the bot with a full window minimax search.
+    pub fn analyze(mut game: Game) -> Analysis {
+        let mut analysis = Analysis {
+            scores: vec![None; game.cells.len()],
+            best: None,
+            principal_variation: Vec::new(),
+        };
+
+        let empty_cells = game.empty_cell_count();
+        if game.winner() != game.empty_mark || empty_cells == 0 {
+            return analysis;
+        }
+
+        let depth = cmp::min(empty_cells, game.max_depth) as i32;
+        let mut search = Search::default();
+        let mut best: Option<(i32, usize)> = None;
+        let bot_mark = game.bot_mark;
+
+        // Every playable cell gets a score, only the moves below the root are pruned
+        for cell in game.playable_cell_indices() {
+            game.make_move(cell, bot_mark);
+            let next_player = game.next_player(bot_mark);
+            let (value, _) = Self::minimax(
+                &mut game,
+                &mut search,
+                next_player,
+                depth - 1,
+                i32::MIN,
+                i32::MAX,
+                next_player == bot_mark,
+            );
+            game.undo_move(cell);
+
+            analysis.scores[cell] = Some(value);
+
+            // Prefer the smallest index of equally good moves to make analysis repeatable
+            if best.is_none_or(|(best_value, best_cell)| {
+                value > best_value || (value == best_value && cell < best_cell)
+            }) {
+                best = Some((value, cell));
+            }
+        }
+
+        if let Some((_, best_cell)) = best {
+            analysis.best = Some(best_cell as u16);
+            analysis.principal_variation =
+                Self::principal_variation(&mut game, slice::from_ref(&search), best_cell, depth)
+                    .into_iter()
+                    .map(|cell| cell as u16)
+                    .collect();
+        }
+
+        analysis
+    }
+
+    /// Bot's move in the opening phase of protocol `opening`.
+    ///
+    /// On `game` the bot plays white and black (player 1) has made the latest
+    /// move. Stones are placed such that the position stays as balanced as
+    /// possible, as the other player chooses the colours after them. Colour is
+    /// chosen by the minimax value of the position with white to move.
+    pub fn opening_move(mut game: Game, opening: OpeningRule) -> OpeningMove {
+        let stones_count = game.marker_count();
+
+        let choice = match stones_count {
+            0 => return Self::place_opening_three(&mut game),
+            3 if opening == OpeningRule::Swap2 => {
+                let value = Self::position_value(&mut game);
+
+                if value.abs() <= SWAP2_BALANCED_MAX_VALUE {
+                    return Self::place_opening_two(&mut game);
+                }
+                Self::opening_colour(value)
+            }
+            _ => Self::opening_colour(Self::position_value(&mut game)),
+        };
+
+        OpeningMove {
+            choice,
+            black: Vec::new(),
+            white: Vec::new(),
+        }
+    }
+
+    fn opening_colour(white_value: i32) -> OpeningChoice {
+        if white_value >= 0 {
+            OpeningChoice::White
+        } else {
+            OpeningChoice::Black
+        }
+    }
+
+    /// Minimax value of the position for the bot, the bot moving next.
+    fn position_value(game: &mut Game) -> i32 {
+        let depth = cmp::min(game.empty_cell_count(), game.max_depth) as i32;
+        let mut searches = Self::thread_searches(game);
+
+        Self::root_search(game, &mut searches, depth, None).map_or(0, |root_move| root_move.value)
+    }
+
+    /// Black stone to the center, white next to it and the second black stone nearby.
+    fn place_opening_three(game: &mut Game) -> OpeningMove {
+        let (black, white) = (game.p1_mark, game.bot_mark);
+        let center = game.rows() / 2 * game.cols() + game.cols() / 2;
+
+        game.make_move(center, black);
+        let white_cell = *Self::empty_cells_near(game, center, 1)
+            .choose(&mut rand::thread_rng())
+            .expect("Center of the board has neighbours");
+
+        game.make_move(white_cell, white);
+        let candidates = Self::empty_cells_near(game, center, 2);
+        let black_cell = Self::most_balanced_move(game, &candidates, black).0;
+
+        OpeningMove {
+            choice: OpeningChoice::PlaceThree,
+            black: vec![center as u16, black_cell as u16],
+            white: vec![white_cell as u16],
+        }
+    }
+
+    /// One more stone of both colours, as balanced as possible.
+    fn place_opening_two(game: &mut Game) -> OpeningMove {
+        let (black, white) = (game.p1_mark, game.bot_mark);
+        let mut best: Option<(i32, usize, usize)> = None;
+
+        let mut white_candidates = game.empty_cell_indices();
+        white_candidates.truncate(SWAP2_CANDIDATE_CELLS);
+
+        for white_cell in white_candidates {
+            game.make_move(white_cell, white);
+
+            let mut black_candidates = game.empty_cell_indices();
+            black_candidates.truncate(SWAP2_CANDIDATE_CELLS);
+            let (black_cell, value) = Self::most_balanced_move(game, &black_candidates, black);
+
+            game.undo_move(white_cell);
+
+            if best.is_none_or(|(best_value, _, _)| value.abs() < best_value) {
+                best = Some((value.abs(), white_cell, black_cell));
+            }
+        }
+
+        let (_, white_cell, black_cell) = best.expect("Opening leaves empty cells");
+
+        OpeningMove {
+            choice: OpeningChoice::PlaceTwo,
+            black: vec![black_cell as u16],
+            white: vec![white_cell as u16],
+        }
+    }
+
+    /// Move of `player` among `candidates` leaving a position valued closest to zero.
+    fn most_balanced_move(game: &mut Game, candidates: &[usize], player: i8) -> (usize, i32) {
+        let mut candidates = candidates.to_vec();
+        candidates.shuffle(&mut rand::thread_rng());
+
+        candidates
+            .into_iter()
+            .map(|cell| {
+                game.make_move(cell, player);
+                let value = Self::position_value(game);
+                game.undo_move(cell);
+                (cell, value)
+            })
+            .min_by_key(|&(_, value)| value.abs())
+            .expect("Opening leaves empty cells")
+    }
+
+    fn empty_cells_near(game: &Game, index: usize, distance: usize) -> Vec<usize> {
+        let (rows, cols) = (game.rows(), game.cols());
+        let (row, col) = (index / cols, index % cols);
+
+        (row.saturating_sub(distance)..cmp::min(row + distance + 1, rows))
+            .flat_map(|r| {
+                (col.saturating_sub(distance)..cmp::min(col + distance + 1, cols))
+                    .map(move |c| r * cols + c)
+            })
+            .filter(|&idx| game.cells[idx] == game.empty_mark)
+            .collect()
+    }
+
+    /// Statistics of the minimax searches, the principal variation only for verbose games.
+    fn record_search_stats(
+        game: &mut Game,
+        searches: &[Search],
+        root_move: Option<RootMove>,
+        stats: &mut SearchStats,
+    ) {
+        stats.nodes = searches.iter().map(|search| search.nodes).sum();
+
+        if let Some(root_move) = root_move {
+            stats.score = Some(root_move.value);
+            stats.depth = Some(root_move.depth as u32);
+
+            if game.verbose {
+                stats.principal_variation =
+                    Self::principal_variation(game, searches, root_move.cell, root_move.depth)
+                        .into_iter()
+                        .map(|cell| cell as u16)
+                        .collect();
+            }
+        }
+    }
+
+    /// Moves following `first_move` by the best moves stored in the transposition tables.
+    fn principal_variation(
+        game: &mut Game,
+        searches: &[Search],
+        first_move: usize,
+        depth: i32,
+    ) -> Vec<usize> {
+        let mut variation = vec![first_move];
+        let mut player = game.bot_mark;
+
+        game.make_move(first_move, player);
+
+        while variation.len() < depth as usize
+            && game.winner_after_move(*variation.last().unwrap()) == game.empty_mark
+        {
+            let hash = game.hash();
+            let next_move = searches
+                .iter()
+                .find_map(|search| search.tt.get(hash).and_then(|entry| entry.best_move))
+                .filter(|&cell| game.cells[cell] == game.empty_mark);
+
+            let Some(next_move) = next_move else {
+                break;
+            };
+            player = game.next_player(player);
+            game.make_move(next_move, player);
+            variation.push(next_move);
+        }
+
+        for &cell in variation.iter().rev() {
+            game.undo_move(cell);
+        }
+        variation
+    }
+
+    fn iterative_deepening(
+        game: &mut Game,
+        searches: &mut [Search],
+        max_depth: usize,
+        time_budget: Duration,
+    ) -> Option<RootMove> {
+        let deadline = Instant::now() + time_budget;
+        let mut best_move: Option<RootMove> = None;
+
+        for depth in 1..=max_depth {
+            let hint_move = best_move.map(|root_move| root_move.cell);
+            let depth_best_move = Self::root_search(game, searches, depth as i32, hint_move);
+
+            if searches.iter().any(|search| search.aborted) {
+                break;
+            }
+            best_move = depth_best_move;
+
+            if best_move.is_none() || Instant::now() >= deadline {
+                break;
+            }
+            // The first iteration is always completed to have at least one move to play
+            for search in searches.iter_mut() {
+                search.deadline = Some(deadline);
+            }
+        }
+
+        best_move
+    }
+
+    fn thread_searches(game: &Game) -> Vec<Search> {
+        (0..cmp::max(game.search_threads, 1))
+            .map(|_| Search::default())
+            .collect()
+    }
+
+    /// Best move for the bot searched `depth` moves ahead, using one thread per search state.
+    fn root_search(
+        game: &mut Game,
+        searches: &mut [Search],
+        depth: i32,
+        hint_move: Option<usize>,
+    ) -> Option<RootMove> {
+        if let [search] = searches {
+            let (value, best_move) =
+                Self::minimax(game, search, game.bot_mark, depth, i32::MIN, i32::MAX, true);
+            return best_move.map(|cell| RootMove { cell, value, depth });
+        }
+
+        if game.winner() != game.empty_mark || depth <= 0 || game.empty_cell_count() == 0 {
+            return None;
+        }
+
+        let mut root_moves = game.empty_cell_indices();
+
+        if let Some(hint_idx) =
+            hint_move.and_then(|hint| root_moves.iter().position(|&c| c == hint))
+        {
+            root_moves[..=hint_idx].rotate_right(1);
+        }
+
+        // Root moves are split between the threads, each having its own copy of the
+        // game and transposition table. Best value so far is shared as the root alpha.
+        let best_value = AtomicI32::new(i32::MIN);
+        let threads_count = searches.len();
+
+        let thread_results: Vec<Option<(i32, usize)>> = thread::scope(|scope| {
+            let handles: Vec<_> = searches
+                .iter_mut()
+                .enumerate()
+                .map(|(thread_idx, search)| {
+                    let mut game = game.clone();
+                    let bot_mark = game.bot_mark;
+                    let (root_moves, best_value) = (&root_moves, &best_value);
+
+                    scope.spawn(move || {
+                        let mut thread_best: Option<(i32, usize)> = None;
+
+                        for (order, &cell) in root_moves
+                            .iter()
+                            .enumerate()
+                            .skip(thread_idx)
+                            .step_by(threads_count)
+                        {
+                            // Values equal to the best one must be exact for tie-breaking by the order
+                            let alpha = best_value.load(Ordering::Relaxed).saturating_sub(1);
+
+                            game.make_move(cell, bot_mark);
+                            let next_player = game.next_player(bot_mark);
+                            let (value, _) = Self::minimax(
+                                &mut game,
+                                search,
+                                next_player,
+                                depth - 1,
+                                alpha,
+                                i32::MAX,
+                                next_player == bot_mark,
+                            );
+                            game.undo_move(cell);
+
+                            if search.aborted {
+                                return None;
+                            }
+                            if thread_best.is_none_or(|(best, _)| value > best) {
+                                thread_best = Some((value, order));
+                                best_value.fetch_max(value, Ordering::Relaxed);
+                            }
+                        }
+                        thread_best
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("search thread panicked"))
+                .collect()
+        });
+
+        // Like the sequential search, prefer the first of equally good moves
+        thread_results
+            .into_iter()
+            .flatten()
+            .max_by_key(|&(value, order)| (value, Reverse(order)))
+            .map(|(value, order)| RootMove {
+                cell: root_moves[order],
+                value,
+                depth,
+            })
+    }
+
+    /// Forced win for the bot or defence against a forced win of p1.
+    ///
+    /// Used only on the large boards for which minimax is too shallow to see
+    /// these sequences. The searches give up when `deadline` passes.
+    fn threat_space_move(game: &mut Game, deadline: Option<Instant>) -> Option<usize> {
+        // Threat sequences assume winning lines, single moves, every empty cell being playable and markers staying put
+        let large_board = game.cells.len() >= THREAT_SPACE_MIN_CELLS
+            && !game.gravity
+            && !game.captures
+            && !game.connect6
+            && !game.misere;
+        if !large_board || !matches!(game.level, Level::Normal) || game.winner() != game.empty_mark
+        {
+            return None;
+        }
+
+        let mut threat_search = ThreatSearch::with_deadline(deadline);
+
+        for mode in [ThreatMode::Vcf, ThreatMode::Vct] {
+            if let Some(win_move) = threat_search.winning_move(game, game.bot_mark, mode) {
+                return Some(win_move);
+            }
+        }
+
+        let p1_mark = game.p1_mark;
+        let p1_wins = |search: &mut ThreatSearch, game: &mut Game, modes: &[ThreatMode]| {
+            modes
+                .iter()
+                .any(|&mode| search.winning_move(game, p1_mark, mode).is_some())
+        };
+
+        let p1_threats: Vec<ThreatMode> = [ThreatMode::Vcf, ThreatMode::Vct]
+            .into_iter()
+            .filter(|&mode| p1_wins(&mut threat_search, game, &[mode]))
+            .collect();
+
+        // Candidates are ordered by their heuristic value, take the best refuting one.
+        // If no move refutes every threat, refute at least the continuous fours.
+        let candidates = game.empty_cell_indices();
+
+        for threats_count in (1..=p1_threats.len()).rev() {
+            let mut refute_search = ThreatSearch::with_deadline(deadline);
+
+            for &idx in candidates.iter() {
+                game.make_move(idx, game.bot_mark);
+                let refuted = !p1_wins(&mut refute_search, game, &p1_threats[..threats_count]);
+                game.undo_move(idx);
+
+                if refute_search.exhausted() {
+                    // Refutation is not certain
+                    break;
+                }
+                if refuted {
+                    return Some(idx);
+                }
+            }
+        }
+        None
+    }
+
+    fn play_game_first_move(mut game: Game) -> BotMove {
+        let center = game.rows() / 2 * game.cols() + game.cols() / 2;
+
+        let next = if game.gravity {
+            game.empty_cell_indices().first().copied()
+        } else if game.misere && game.cells[center] == game.empty_mark {
+            // Center allows answering every move symmetrically, never completing a line first
+            Some(center)
+        } else {
+            let empty_cells: Vec<usize> = (0..game.cells.len())
+                .filter(|&idx| game.cells[idx] == game.empty_mark)
+                .collect();
+            empty_cells.choose(&mut rand::thread_rng()).copied()
+        };
+
+        let Some(next) = next else {
+            // Every cell is blocked
+            return Self::complete_bot_move(game, None);
+        };
+
         BotMove {
-            next: rand::thread_rng().gen_range(0..cells_count as u8),
+            next: next as u16,
             next_is_valid: true,
             game_over: false,
             winner: game.orig_empty_mark,
+            stats: None,
+            moves: Vec::new(),
+            column: None,
+            captured: Vec::new(),
         }
     }
 
     fn play_bot_first_move_if_defined(game: &Game) -> Option<BotMove> {
         let p1_mark_pos = game.cells.iter().position(|&cell| cell == game.p1_mark);
 
-        let bot_next_pos = match (p1_mark_pos, game.board_size) {
-            (Some(p1_idx), BoardSize::X55) => FirstMove::find_bot_first_move_5x5(p1_idx),
+        let bot_next_pos = match (p1_mark_pos, game.rows(), game.cols()) {
+            (Some(p1_idx), 5, 5)
+                if !game.gravity
+                    && !game.misere
+                    && game.topology == Topology::Plane
+                    && !game.has_blocked_cells() =>
+            {
+                FirstMove::find_bot_first_move_5x5(p1_idx)
+            }
             _ => return None,
         };
 
@@ -101,6 +687,10 @@ impl Bot {
             next_is_valid: true,
             game_over: false,
             winner: game.orig_empty_mark,
+            stats: None,
+            moves: Vec::new(),
+            column: None,
+            captured: Vec::new(),
         })
     }
 
@@ -133,75 +723,148 @@ impl Bot {
     fn complete_bot_move(mut game: Game, best_next_move: Option<usize>) -> BotMove {
         match best_next_move {
             Some(best_move) => {
-                game.cells[best_move] = game.bot_mark;
+                game.make_move(best_move, game.bot_mark);
 
-                let winner = game.winner();
+                let captured = game.last_captures().iter().map(|&idx| idx as u16).collect();
+                let winner = game.game_winner(game.winner());
                 let game_over = winner != game.empty_mark || game.empty_cell_count() == 0;
                 let winner_orig = Self::renormalize_winner_marker(&game, winner);
 
                 BotMove {
-                    next: best_move as u8,
+                    next: best_move as u16,
                     next_is_valid: true,
                     game_over,
                     winner: winner_orig,
+                    stats: None,
+                    moves: Vec::new(),
+                    column: None,
+                    captured,
                 }
             }
             None => BotMove {
-                next: u8::MAX,
+                next: u16::MAX,
                 next_is_valid: false,
                 game_over: true,
-                winner: Self::renormalize_winner_marker(&game, game.winner()),
+                winner: Self::renormalize_winner_marker(&game, game.game_winner(game.winner())),
+                stats: None,
+                moves: Vec::new(),
+                column: None,
+                captured: Vec::new(),
             },
         }
     }
 
     fn minimax(
         game: &mut Game,
+        search: &mut Search,
         player: i8,
         depth: i32,
         mut alpha: i32,
         mut beta: i32,
         maximize: bool,
     ) -> (i32, Option<usize>) {
-        let winner = game.winner();
+        search.nodes += 1;
+
+        if search
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            search.aborted = true;
+        }
+        if search.aborted {
+            return (0, None);
+        }
+
+        // Only the root position may have a win made by some earlier move
+        let winner = match game.last_move() {
+            Some(last_move) => game.winner_after_move(last_move),
+            None => game.winner(),
+        };
 
         if winner != game.empty_mark || depth <= 0 || game.empty_cell_count() == 0 {
             return (game.heuristic_game_value(winner, depth), None);
         }
 
+        // Side to move is not hashed as it's determined by the marker counts
+        let hash = game.hash();
+        let (alpha_orig, beta_orig) = (alpha, beta);
+        let mut hint_move = None;
+
+        if let Some(entry) = search.tt.get(hash) {
+            hint_move = entry.best_move;
+
+            if entry.depth == depth {
+                match entry.bound {
+                    Bound::Exact => return (entry.value, entry.best_move),
+                    Bound::Lower => alpha = cmp::max(alpha, entry.value),
+                    Bound::Upper => beta = cmp::min(beta, entry.value),
+                }
+                if beta <= alpha {
+                    return (entry.value, entry.best_move);
+                }
+            }
+        }
+
         let mut best_entry = None;
         let mut best_value = if maximize { i32::MIN } else { i32::MAX };
 
-        let empty_cells = game.empty_cell_indices();
+        let mut empty_cells = game.empty_cell_indices();
+
+        if let Some(hint_idx) =
+            hint_move.and_then(|hint| empty_cells.iter().position(|&c| c == hint))
+        {
+            empty_cells[..=hint_idx].rotate_right(1);
+        }
 
         for &empty_cell in empty_cells.iter() {
-            game.cells[empty_cell] = player;
+            game.make_move(empty_cell, player);
 
-            let next_player = if player == game.bot_mark {
-                game.p1_mark
-            } else {
-                game.bot_mark
-            };
+            let next_player = game.next_player(player);
+            let next_maximize = next_player == game.bot_mark;
 
             if maximize {
-                let (value, _) = Self::minimax(game, next_player, depth - 1, alpha, beta, false);
+                let (value, _) = Self::minimax(
+                    game,
+                    search,
+                    next_player,
+                    depth - 1,
+                    alpha,
+                    beta,
+                    next_maximize,
+                );
+                game.undo_move(empty_cell);
+
+                if search.aborted {
+                    return (0, None);
+                }
                 if value > best_value {
                     best_value = value;
                     best_entry = Some(empty_cell);
                 }
-                game.cells[empty_cell] = game.empty_mark;
 
                 alpha = cmp::max(alpha, best_value);
                 if beta <= alpha {
                     break;
                 }
             } else {
-                let (value, _) = Self::minimax(game, next_player, depth - 1, alpha, beta, true);
+                let (value, _) = Self::minimax(
+                    game,
+                    search,
+                    next_player,
+                    depth - 1,
+                    alpha,
+                    beta,
+                    next_maximize,
+                );
+                game.undo_move(empty_cell);
+
+                if search.aborted {
+                    return (0, None);
+                }
                 if value < best_value {
                     best_value = value;
                     best_entry = Some(empty_cell);
                 }
-                game.cells[empty_cell] = game.empty_mark;
 
                 beta = cmp::min(beta, best_value);
                 if beta <= alpha {
@@ -210,6 +873,23 @@ impl Bot {
             }
         }
 
+        let bound = if best_value <= alpha_orig {
+            Bound::Upper
+        } else if best_value >= beta_orig {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        search.tt.store(
+            hash,
+            TtEntry {
+                depth,
+                value: best_value,
+                bound,
+                best_move: best_entry,
+            },
+        );
+
         (best_value, best_entry)
     }
 }
@@ -217,8 +897,10 @@ impl Bot {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::conf::*;
-    use crate::models::{Board, Level};
+    use crate::{
+        conf::GameInitError,
+        models::{Board, Level, OpeningBoard, Topology, WinRule},
+    };
     use std::{cmp::Ordering, mem};
 
     fn init_game(
@@ -234,6 +916,7 @@ mod tests {
             p1_mark,
             bot_mark,
             empty_mark,
+            ..Default::default()
         };
         match Game::new(board, Level::Normal) {
             Ok(game) => game,
@@ -249,6 +932,7 @@ mod tests {
 
         let (_, best_move) = Bot::minimax(
             &mut game,
+            &mut Search::default(),
             first_player,
             init_depth as i32,
             i32::MIN,
@@ -262,7 +946,7 @@ mod tests {
     #[test]
     fn bot_make_win_move_3x3() {
         let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
 
         let cells_collections: [[i8; 9]; 12] = [
             [-1, 1, -1, 0, 1, 0, 1, 0, -1],
@@ -295,7 +979,7 @@ mod tests {
     #[test]
     fn bot_prevent_win_move_3x3() {
         let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
 
         let cells_collections: [[i8; 9]; 12] = [
             [1, -1, -1, -1, -1, 1, 1, 0, 0],
@@ -328,7 +1012,7 @@ mod tests {
     #[test]
     fn bot_prevent_early_win_move_3x3() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
 
         let cells_collections: [[i8; 9]; 5] = [
             [1, 0, -1, 0, 0, 0, 0, 0, 1],
@@ -354,7 +1038,7 @@ mod tests {
     #[test]
     fn bot_make_win_move_5x5() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let cells_collections: [[i8; 25]; 8] = [
             [
@@ -399,7 +1083,7 @@ mod tests {
     #[test]
     fn bot_prevent_win_move_5x5() {
         let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let cells_collections: [[i8; 25]; 8] = [
             [
@@ -441,98 +1125,1019 @@ mod tests {
         }
     }
 
-    #[test]
-    fn bot_game_final_move() {
-        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
-
-        let cells_collections: [[i8; 9]; 3] = [
-            [-1, 1, -1, -1, 1, 1, 1, -1, 0],
-            [1, 1, 0, -1, -1, 1, 1, -1, -1],
-            [1, -1, 0, 0, 1, -1, 0, 0, 0],
-        ];
-        let correct_next_move: [u8; 3] = [8, 2, 8];
-        let correct_winner: [i8; 3] = [empty_mark, bot_mark, bot_mark];
-
-        let it = cells_collections.iter().zip(0..correct_next_move.len());
-
-        for (cells, i) in it {
-            let game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
-
-            let bot_move = Bot::next_move(game);
-
-            assert_eq!(bot_move.next, correct_next_move[i]);
-            assert_eq!(bot_move.winner, correct_winner[i]);
-            assert_eq!(bot_move.next_is_valid, true);
-            assert_eq!(bot_move.game_over, true);
+    fn run_hint_for_p1(cells: &[i8], p1_mark: i8, bot_mark: i8, cells_to_win: u8) -> u16 {
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win,
+            p1_mark,
+            bot_mark,
+            empty_mark: 0,
+            ..Default::default()
+        };
+        match Game::new(board.players_swapped(), Level::Normal) {
+            Ok(game) => Bot::next_move(game).next,
+            Err(error_kind) => panic!("Game::new(): {:?}", error_kind),
         }
     }
 
+    // Hint tests use positions of the bot's tests above with the roles of the players swapped
+
     #[test]
-    fn bot_game_final_move_markers_correct() {
-        let (p1_mark, bot_mark, empty_mark) = (5, -3, 2);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+    fn hint_make_win_move_3x3() {
+        let (p1_mark, bot_mark) = (1, -1);
+        let cells_to_win = 3;
 
-        let cells_collections: [[i8; 9]; 2] = [
-            [5, 5, -3, 2, -3, 2, 2, 2, 2],
-            [5, -3, -3, -3, -3, 5, 5, 5, 2],
+        let cells_collections: [[i8; 9]; 4] = [
+            [-1, 1, -1, 0, 1, 0, 1, 0, -1],
+            [-1, 1, 0, 0, 1, 0, -1, 0, 0],
+            [1, 1, 0, 0, -1, 0, 0, 0, -1],
+            [0, 0, 0, -1, -1, 0, 0, 1, 1],
         ];
+        let correct_win_moves: [u16; 4] = [7, 7, 2, 6];
 
-        let correct_next_move: [u8; 2] = [6, 8];
-        let correct_winner: [i8; 2] = [bot_mark, empty_mark];
-
-        let it = cells_collections.iter().zip(0..correct_next_move.len());
-
-        for (cells, i) in it {
-            let game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
-
-            let bot_move = Bot::next_move(game);
+        let it = cells_collections.iter().zip(correct_win_moves.iter());
 
-            // Check that player markers are the original and not the normalized
-            assert_eq!(bot_move.next, correct_next_move[i]);
-            assert_eq!(bot_move.winner, correct_winner[i]);
-            assert_eq!(bot_move.next_is_valid, true);
-            assert_eq!(bot_move.game_over, true);
+        for (i, (cells, &correct_move)) in it.enumerate() {
+            let hint = run_hint_for_p1(cells, p1_mark, bot_mark, cells_to_win);
+            assert_eq!(hint, correct_move, "collection {}", i + 1);
         }
     }
 
     #[test]
-    fn bot_not_game_final_move() {
-        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+    fn hint_prevent_win_move_3x3() {
+        let (p1_mark, bot_mark) = (1, -1);
+        let cells_to_win = 3;
 
-        let cells_collections: [[i8; 9]; 3] = [
-            [1, 0, -1, -1, 1, 0, 1, 0, -1],
-            [1, -1, 1, 0, 1, 0, -1, 0, -1],
-            [-1, 0, 0, 0, 0, 0, 1, 0, -1],
+        let cells_collections: [[i8; 9]; 4] = [
+            [1, -1, -1, -1, -1, 1, 1, 0, 0],
+            [1, -1, 0, 0, -1, 1, 0, 0, 0],
+            [0, 1, -1, 0, -1, 1, 1, 0, -1],
+            [-1, 1, -1, 1, -1, 0, 1, 0, 0],
         ];
-        let correct_next_move: [u8; 3] = [5, 7, 4];
-
-        let it = cells_collections.iter().zip(0..correct_next_move.len());
-
-        for (cells, i) in it {
-            let game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+        let correct_moves: [u16; 4] = [7, 7, 0, 8];
 
-            let bot_move = Bot::next_move(game);
+        let it = cells_collections.iter().zip(correct_moves.iter());
 
-            assert_eq!(bot_move.next, correct_next_move[i]);
-            assert_eq!(bot_move.winner, empty_mark);
-            assert_eq!(bot_move.next_is_valid, true);
-            assert_eq!(bot_move.game_over, false);
+        for (i, (cells, &correct_move)) in it.enumerate() {
+            let hint = run_hint_for_p1(cells, p1_mark, bot_mark, cells_to_win);
+            assert_eq!(hint, correct_move, "collection {}", i + 1);
         }
     }
 
     #[test]
-    fn bot_game_already_over() {
-        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+    fn hint_make_win_move_5x5() {
+        let (p1_mark, bot_mark) = (-1, 1);
+        let cells_to_win = 4;
 
         let cells_collections: [[i8; 25]; 3] = [
             [
-                0, 1, 1, 0, 0, 0, -1, -1, -1, -1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1, 0, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
             ],
             [
-                0, 0, 1, -1, -1, 0, 0, 1, -1, 0, 0, 0, 1, -1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0,
+                1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1, -1, -1, 0, 1, 0, 0, 0, 0, 0,
+            ],
+            [
+                0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, -1, 0, 0, 0, -1, 1, 0, 0, -1, 0, 0, 0,
+            ],
+        ];
+        let correct_win_moves: [u16; 3] = [12, 18, 9];
+
+        let it = cells_collections.iter().zip(correct_win_moves.iter());
+
+        for (i, (cells, &correct_move)) in it.enumerate() {
+            let hint = run_hint_for_p1(cells, p1_mark, bot_mark, cells_to_win);
+            assert_eq!(hint, correct_move, "collection {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn hint_prevent_win_move_5x5() {
+        let (p1_mark, bot_mark) = (1, -1);
+        let cells_to_win = 4;
+
+        let cells_collections: [[i8; 25]; 3] = [
+            [
+                -1, 0, -1, -1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            [
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1, 1, 0, 0, 0, 0, -1, 1, 0, 0, 0, 0, -1, 0,
+            ],
+            [
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, -1, -1, -1, 0,
+            ],
+        ];
+        let correct_moves: [u16; 3] = [1, 5, 24];
+
+        let it = cells_collections.iter().zip(correct_moves.iter());
+
+        for (i, (cells, &correct_move)) in it.enumerate() {
+            let hint = run_hint_for_p1(cells, p1_mark, bot_mark, cells_to_win);
+            assert_eq!(hint, correct_move, "collection {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn hint_with_last_move_of_bot_15x15() {
+        let (p1_mark, bot_mark) = (-1, 1);
+        // Bot is black and its latest move 99 made a four, which player 1 must block
+        let cells = init_15x15_cells(&[96, 97, 98, 99], &[95, 0, 224], p1_mark, bot_mark);
+
+        for win_rule in [WinRule::Freestyle, WinRule::Renju] {
+            let board = |last_move| Board {
+                cells: cells.clone(),
+                cells_to_win: 5,
+                p1_mark,
+                bot_mark,
+                empty_mark: 0,
+                win_rule,
+                last_move,
+                ..Default::default()
+            };
+
+            let game = Game::new(board(Some(99)).players_swapped(), Level::Normal).unwrap();
+            assert_eq!(Bot::next_move(game).next, 100, "{:?}", win_rule);
+
+            // Player 1's stone isn't the latest move when it's player 1's turn
+            assert!(matches!(
+                Game::new(board(Some(95)).players_swapped(), Level::Normal),
+                Err(GameInitError::Inconsistent)
+            ));
+        }
+    }
+
+    #[test]
+    fn analyze_make_win_move_3x3() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let cells: [i8; 9] = [1, -1, 0, 0, 1, -1, 0, 0, 0];
+        let game = init_game(&cells, p1_mark, bot_mark, empty_mark, 3);
+
+        let analysis = Bot::analyze(game);
+
+        assert_eq!(analysis.best, Some(8));
+        assert_eq!(analysis.principal_variation, vec![8]);
+
+        for (cell, score) in analysis.scores.iter().enumerate() {
+            assert_eq!(score.is_some(), cells[cell] == empty_mark, "cell {}", cell);
+        }
+        let win_score = analysis.scores[8].unwrap();
+        assert!(analysis
+            .scores
+            .iter()
+            .flatten()
+            .all(|&score| score <= win_score));
+    }
+
+    #[test]
+    fn analyze_prevent_win_move_3x3() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let cells: [i8; 9] = [-1, 0, 0, 0, -1, 1, 0, 0, 0];
+        let game = init_game(&cells, p1_mark, bot_mark, empty_mark, 3);
+
+        let analysis = Bot::analyze(game);
+        let pv = &analysis.principal_variation;
+
+        assert_eq!(analysis.best, Some(8));
+        assert_eq!(pv[0], 8);
+        assert!(pv.len() > 1);
+        assert!(pv.iter().all(|&cell| cells[cell as usize] == empty_mark));
+        assert_eq!(
+            pv.len(),
+            pv.iter().collect::<std::collections::HashSet<_>>().len()
+        );
+    }
+
+    #[test]
+    fn analyze_score_every_empty_cell_19x19() {
+        let mut cells = vec![0; 19 * 19];
+        for (i, idx) in [40, 60, 100, 180, 250, 300].into_iter().enumerate() {
+            cells[idx] = if i % 2 == 0 { -1 } else { 1 };
+        }
+        let game = init_game(&cells, -1, 1, 0, 5);
+
+        let analysis = Bot::analyze(game);
+
+        for (cell, score) in analysis.scores.iter().enumerate() {
+            assert_eq!(score.is_some(), cells[cell] == 0, "cell {}", cell);
+        }
+    }
+
+    #[test]
+    fn analyze_game_already_over() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let cells: [i8; 9] = [1, 1, 1, -1, -1, 0, -1, 0, 0];
+        let game = init_game(&cells, p1_mark, bot_mark, empty_mark, 3);
+
+        let analysis = Bot::analyze(game);
+
+        assert_eq!(analysis.best, None);
+        assert!(analysis.principal_variation.is_empty());
+        assert!(analysis.scores.iter().all(|score| score.is_none()));
+    }
+
+    #[test]
+    fn bot_search_reuses_transposition_table() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let cells_to_win = 4;
+        let cells: [i8; 25] = [
+            0, 0, 0, 0, 0, 0, 1, 0, -1, 0, 0, -1, -1, 1, 0, 0, 1, -1, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let mut game = init_game(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+        let mut search = Search::default();
+        let depth = game.max_depth as i32;
+
+        let (value, best_move) = Bot::minimax(
+            &mut game,
+            &mut search,
+            bot_mark,
+            depth,
+            i32::MIN,
+            i32::MAX,
+            true,
+        );
+        let first_nodes = search.nodes;
+
+        // Root position is now stored as an exact entry
+        let (value_again, best_move_again) = Bot::minimax(
+            &mut game,
+            &mut search,
+            bot_mark,
+            depth,
+            i32::MIN,
+            i32::MAX,
+            true,
+        );
+
+        assert_eq!(value, value_again);
+        assert_eq!(best_move, best_move_again);
+        assert_eq!(search.nodes, first_nodes + 1);
+        assert_eq!(game.cells, cells);
+    }
+
+    #[test]
+    fn bot_iterative_deepening_make_win_move_5x5() {
+        let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
+        let cells_to_win = 4;
+
+        let cells_collections: [[i8; 25]; 3] = [
+            [
+                1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1, 0, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            [
+                1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1, -1, -1, 0, 1, 0, 0, 0, 0, 0,
+            ],
+            [
+                0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, -1, 0, 0, 0, -1, 1, 0, 0, -1, 0, 0, 0,
+            ],
+        ];
+        let correct_win_moves: [u16; 3] = [12, 18, 9];
+
+        let it = cells_collections.iter().zip(correct_win_moves.iter());
+
+        for (i, (cells, &correct_move)) in it.enumerate() {
+            let mut game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+            game.time_budget = Some(Duration::from_secs(10));
+
+            let bot_move = Bot::next_move(game);
+            assert_eq!(bot_move.next, correct_move, "collection {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn bot_parallel_search_make_win_move_5x5() {
+        let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
+        let cells_to_win = 4;
+        let cells: [i8; 25] = [
+            0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, -1, 0, 0, 0, -1, 1, 0, 0, -1, 0, 0, 0,
+        ];
+
+        for time_budget in [None, Some(Duration::from_secs(10))] {
+            let mut game = init_game(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+            game.search_threads = 3;
+            game.time_budget = time_budget;
+
+            let bot_move = Bot::next_move(game);
+            assert_eq!(bot_move.next, 9, "time budget {:?}", time_budget);
+        }
+    }
+
+    #[test]
+    fn bot_root_search_agrees_with_minimax() {
+        let cells_collections: [(&[i8], i8, i8, u8); 6] = [
+            (&[-1, 1, -1, 0, 1, 0, 1, 0, -1], -1, 1, 3),
+            (&[0, 1, 1, -1, -1, 0, 1, -1, 0], -1, 1, 3),
+            (&[1, -1, 0, 0, -1, 0, 1, 0, 0], -1, 1, 3),
+            (&[0, -1, 0, 1, 0, 0, 0, -1, 1], -1, 1, 3),
+            (
+                &[
+                    1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1, 0, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+                ],
+                1,
+                -1,
+                4,
+            ),
+            (
+                &[
+                    0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, -1, 0, 0, 0, -1, 1, 0, 0, -1, 0, 0, 0,
+                ],
+                1,
+                -1,
+                4,
+            ),
+        ];
+
+        for (i, &(cells, p1_mark, bot_mark, cells_to_win)) in cells_collections.iter().enumerate() {
+            let game = init_game(cells, p1_mark, bot_mark, 0, cells_to_win);
+            let depth = cmp::min(game.empty_cell_count(), game.max_depth) as i32;
+
+            let mut sequential_game = game.clone();
+            let (_, best_move) = Bot::minimax(
+                &mut sequential_game,
+                &mut Search::default(),
+                game.bot_mark,
+                depth,
+                i32::MIN,
+                i32::MAX,
+                true,
+            );
+
+            let mut parallel_game = game;
+            let mut searches: Vec<Search> = (0..4).map(|_| Search::default()).collect();
+            let parallel_best_move =
+                Bot::root_search(&mut parallel_game, &mut searches, depth, None)
+                    .map(|root_move| root_move.cell);
+
+            assert_eq!(parallel_best_move, best_move, "collection {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn bot_make_win_move_6x7() {
+        #[rustfmt::skip]
+        let cells: [i8; 42] = [
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0,
+            1, 0, 0, 0, 0, 0, 0,
+            1, 0, 0, 0, 0, -1, 0,
+            1, 0, -1, -1, 0, 0, -1,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(6),
+            cols: Some(7),
+            ..Default::default()
+        };
+        let game = Game::new(board, Level::Normal).unwrap();
+
+        let bot_move = Bot::next_move(game);
+        assert_eq!(bot_move.next, 14);
+        assert!(bot_move.game_over);
+    }
+
+    #[test]
+    fn bot_gravity_move_6x7() {
+        // Without gravity the bot would win at cell 19, which has no support
+        #[rustfmt::skip]
+        let cells: [i8; 42] = [
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 1, 0, 0,
+            0, 0, 0, 1, -1, 0, 0,
+            0, 0, 1, -1, -1, -1, 0,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(6),
+            cols: Some(7),
+            gravity: true,
+            ..Default::default()
+        };
+        let game = Game::new(board, Level::Normal).unwrap();
+
+        let bot_move = Bot::next_move(game);
+        // Block the bottom row of p1
+        assert_eq!(bot_move.next, 41);
+        assert_eq!(bot_move.column, Some(6));
+        assert!(!bot_move.game_over);
+    }
+
+    fn init_connect6_game(p1_cells: &[usize], bot_cells: &[usize]) -> Game {
+        let mut cells = vec![0; 361];
+        for &idx in p1_cells {
+            cells[idx] = -1;
+        }
+        for &idx in bot_cells {
+            cells[idx] = 1;
+        }
+        let board = Board {
+            cells,
+            cells_to_win: 6,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(19),
+            cols: Some(19),
+            connect6: true,
+            ..Default::default()
+        };
+        Game::new(board, Level::Normal).unwrap()
+    }
+
+    #[test]
+    fn bot_connect6_win_with_two_moves_19x19() {
+        // Bot's four on row 9 becomes six with two more markers
+        let game = init_connect6_game(
+            &[0, 18, 342, 360, 100],
+            &[9 * 19 + 5, 9 * 19 + 6, 9 * 19 + 7, 9 * 19 + 8],
+        );
+
+        let bot_move = Bot::next_move(game);
+        assert_eq!(bot_move.moves.len(), 2);
+        assert_eq!(bot_move.next, bot_move.moves[0]);
+        assert!(bot_move.game_over);
+        assert_eq!(bot_move.winner, 1);
+    }
+
+    #[test]
+    fn bot_connect6_block_open_five_19x19() {
+        // P1's five on row 3 is open at both ends, both must be blocked
+        let game = init_connect6_game(
+            &[3 * 19 + 5, 3 * 19 + 6, 3 * 19 + 7, 3 * 19 + 8, 3 * 19 + 9],
+            &[360, 342, 18, 300],
+        );
+
+        let bot_move = Bot::next_move(game);
+        let mut moves = bot_move.moves.clone();
+        moves.sort();
+        assert_eq!(moves, vec![3 * 19 + 4, 3 * 19 + 10]);
+        assert!(!bot_move.game_over);
+    }
+
+    #[test]
+    fn bot_connect6_first_move_is_single() {
+        let game = init_connect6_game(&[], &[]);
+
+        let bot_move = Bot::next_move(game);
+        assert_eq!(bot_move.moves, vec![bot_move.next]);
+    }
+
+    #[test]
+    fn bot_make_win_move_across_edge_torus_5x5() {
+        // Bot's three in the 2nd row wrap over the right edge to its start
+        #[rustfmt::skip]
+        let cells: [i8; 25] = [
+            -1, -1, 0, 0, -1,
+             1,  0, -1, 1, 1,
+             0,  0, 0, 0, 0,
+             0,  0, 0, 0, 0,
+             0,  0, 0, 0, 0,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            topology: Topology::Torus,
+            ..Default::default()
+        };
+        let game = Game::new(board, Level::Normal).unwrap();
+
+        let bot_move = Bot::next_move(game);
+        assert_eq!(bot_move.next, 6);
+        assert!(bot_move.game_over);
+        assert_eq!(bot_move.winner, 1);
+    }
+
+    #[test]
+    fn bot_make_win_move_around_blocked_cell_5x5() {
+        // Cell 8 would complete the 2nd row without the blocked cell
+        #[rustfmt::skip]
+        let cells: [i8; 25] = [
+             0,  0,  0, 0,  1,
+             1,  1,  1, 9,  1,
+            -1,  0, -1, 0,  1,
+             0, -1,  0, 0,  0,
+            -1,  0, -1, 0, -1,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            blocked_mark: Some(9),
+            ..Default::default()
+        };
+        let game = Game::new(board, Level::Normal).unwrap();
+
+        let bot_move = Bot::next_move(game);
+        assert_eq!(bot_move.next, 19);
+        assert!(bot_move.game_over);
+        assert_eq!(bot_move.winner, 1);
+    }
+
+    #[test]
+    fn bot_move_markers_walled_in_5x5() {
+        #[rustfmt::skip]
+        let cells: [i8; 25] = [
+            -1, 9, 0, 9, -1,
+             9, 9, 0, 9,  9,
+             0, 0, 0, 0,  0,
+             0, 0, 0, 9,  9,
+             0, 0, 0, 9,  1,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            blocked_mark: Some(9),
+            ..Default::default()
+        };
+
+        for engine in [Engine::Minimax, Engine::Mcts] {
+            let mut game = Game::new(board.clone(), Level::Normal).unwrap();
+            game.engine = engine;
+
+            let bot_move = Bot::next_move(game);
+            assert!(bot_move.next_is_valid, "{:?}", engine);
+            assert!(!bot_move.game_over, "{:?}", engine);
+            assert_eq!(cells[bot_move.next as usize], 0, "{:?}", engine);
+        }
+    }
+
+    #[test]
+    fn bot_misere_avoid_line_3x3() {
+        let init_misere_game = |cells: [i8; 9]| {
+            let board = Board {
+                cells: cells.to_vec(),
+                cells_to_win: 3,
+                p1_mark: -1,
+                bot_mark: 1,
+                empty_mark: 0,
+                misere: true,
+                ..Default::default()
+            };
+            Game::new(board, Level::Normal).unwrap()
+        };
+
+        // Cell 2 would complete the bot's top row
+        let bot_move = Bot::next_move(init_misere_game([1, 1, 0, 0, -1, 0, -1, 0, 0]));
+        assert_ne!(bot_move.next, 2);
+        assert!(!bot_move.game_over);
+
+        // Forced to complete the line, the bot loses
+        let bot_move = Bot::next_move(init_misere_game([1, 1, 0, -1, -1, 1, -1, 1, -1]));
+        assert_eq!(bot_move.next, 2);
+        assert!(bot_move.game_over);
+        assert_eq!(bot_move.winner, -1);
+    }
+
+    #[test]
+    fn bot_capture_fifth_pair_9x9() {
+        let mut cells = [0; 81];
+        cells[0] = 1;
+        cells[1] = -1;
+        cells[2] = -1;
+        cells[40] = -1;
+        cells[50] = 1;
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 5,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(9),
+            cols: Some(9),
+            captures: true,
+            p1_captured_pairs: 4,
+            bot_captured_pairs: 4,
+            ..Default::default()
+        };
+        let game = Game::new(board, Level::Normal).unwrap();
+
+        let bot_move = Bot::next_move(game);
+        assert_eq!(bot_move.next, 3);
+        assert_eq!(bot_move.captured, vec![2, 1]);
+        assert!(bot_move.game_over);
+        assert_eq!(bot_move.winner, 1);
+    }
+
+    #[test]
+    fn bot_verbose_search_stats_5x5() {
+        let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
+        let cells_to_win = 4;
+        let cells: [i8; 25] = [
+            0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, -1, 0, 0, 0, -1, 1, 0, 0, -1, 0, 0, 0,
+        ];
+
+        for time_budget in [None, Some(Duration::from_secs(10))] {
+            let mut game = init_game(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+            game.time_budget = time_budget;
+            game.verbose = true;
+
+            let bot_move = Bot::next_move(game);
+            let stats = bot_move.stats.expect("verbose move has stats");
+
+            assert_eq!(bot_move.next, 9, "time budget {:?}", time_budget);
+            assert_eq!(stats.principal_variation.first(), Some(&9));
+            assert!(stats.score.is_some_and(|score| score > 0));
+            assert!(stats.depth.is_some_and(|depth| depth >= 1));
+            assert!(stats.nodes > 0);
+            assert!(stats
+                .principal_variation
+                .iter()
+                .all(|&cell| cells[cell as usize] == empty_mark));
+        }
+
+        let game = init_game(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+        assert!(Bot::next_move(game).stats.is_none());
+    }
+
+    #[test]
+    fn bot_mcts_engine_make_win_move_5x5() {
+        let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
+        let cells_to_win = 4;
+
+        let cells_collections: [[i8; 25]; 2] = [
+            [
+                1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1, 0, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            [
+                1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1, -1, -1, 0, 1, 0, 0, 0, 0, 0,
+            ],
+        ];
+        let correct_win_moves: [u16; 2] = [12, 18];
+
+        let it = cells_collections.iter().zip(correct_win_moves.iter());
+
+        for (i, (cells, &correct_move)) in it.enumerate() {
+            let mut game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+            game.engine = Engine::Mcts;
+
+            let bot_move = Bot::next_move(game);
+            assert_eq!(bot_move.next, correct_move, "collection {}", i + 1);
+            assert!(bot_move.game_over);
+        }
+    }
+
+    #[test]
+    fn bot_iterative_deepening_zero_time_budget() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let cells_to_win = 4;
+        let cells: [i8; 25] = [
+            0, 0, 0, 0, -1, 0, 1, -1, 0, 0, 0, -1, 1, 1, 0, 0, -1, 1, -1, 0, 0, 0, 0, 0, 0,
+        ];
+        let mut game = init_game(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+        game.time_budget = Some(Duration::ZERO);
+
+        // At least the first iteration must complete
+        let bot_move = Bot::next_move(game);
+        assert!(bot_move.next_is_valid);
+        assert_eq!(cells[bot_move.next as usize], empty_mark);
+    }
+
+    fn init_15x15_cells(
+        bot_cells: &[usize],
+        p1_cells: &[usize],
+        p1_mark: i8,
+        bot_mark: i8,
+    ) -> Vec<i8> {
+        let mut cells = vec![0; 15 * 15];
+
+        for &idx in bot_cells.iter() {
+            cells[idx] = bot_mark;
+        }
+        for &idx in p1_cells.iter() {
+            cells[idx] = p1_mark;
+        }
+        cells
+    }
+
+    #[test]
+    fn bot_make_win_move_19x19() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let mut cells = vec![empty_mark; 19 * 19];
+        // Four in the last row, indices of which don't fit to u8
+        for idx in [352, 353, 354, 355] {
+            cells[idx] = bot_mark;
+        }
+        for idx in [0, 40, 180, 200, 300] {
+            cells[idx] = p1_mark;
+        }
+        let game = init_game(&cells, p1_mark, bot_mark, empty_mark, 5);
+
+        let bot_move = Bot::next_move(game);
+        assert!([351, 356].contains(&bot_move.next));
+        assert!(bot_move.game_over);
+    }
+
+    #[test]
+    fn bot_avoid_overline_exact_rule_15x15() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        // Playing to cell 110 would make six in a row, only cell 105 wins
+        let mut cells = vec![empty_mark; 225];
+        for idx in [106, 107, 108, 109, 111] {
+            cells[idx] = bot_mark;
+        }
+        for idx in [0, 4, 30, 60, 150, 200] {
+            cells[idx] = p1_mark;
+        }
+        let board = Board {
+            cells,
+            cells_to_win: 5,
+            p1_mark,
+            bot_mark,
+            empty_mark,
+            win_rule: WinRule::Exact,
+            ..Default::default()
+        };
+        let game = Game::new(board, Level::Normal).unwrap();
+
+        let bot_move = Bot::next_move(game);
+        assert_eq!(bot_move.next, 105);
+        assert!(bot_move.game_over);
+        assert_eq!(bot_move.winner, bot_mark);
+    }
+
+    #[test]
+    fn bot_ignore_blocked_line_caro_rule_15x15() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        // Bot's five by cell 112 would be blocked at both ends, p1 must be stopped at cell 170
+        let mut cells = vec![empty_mark; 225];
+        for idx in [108, 109, 110, 111, 165] {
+            cells[idx] = bot_mark;
+        }
+        for idx in [107, 113, 166, 167, 168, 169] {
+            cells[idx] = p1_mark;
+        }
+
+        for (win_rule, expected_move) in [(WinRule::Freestyle, 112), (WinRule::Caro, 170)] {
+            let board = Board {
+                cells: cells.clone(),
+                cells_to_win: 5,
+                p1_mark,
+                bot_mark,
+                empty_mark,
+                win_rule,
+                ..Default::default()
+            };
+            let game = Game::new(board, Level::Normal).unwrap();
+
+            let bot_move = Bot::next_move(game);
+            assert_eq!(bot_move.next, expected_move, "{:?}", win_rule);
+        }
+    }
+
+    #[test]
+    fn bot_avoid_forbidden_move_renju_15x15() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        // Bot is black and cell 112 would make an overline, other end of the four is blocked
+        let mut cells = vec![empty_mark; 225];
+        for idx in [108, 109, 110, 111, 113] {
+            cells[idx] = bot_mark;
+        }
+        for idx in [107, 0, 14, 210, 224] {
+            cells[idx] = p1_mark;
+        }
+
+        for win_rule in [WinRule::Freestyle, WinRule::Renju] {
+            let board = Board {
+                cells: cells.clone(),
+                cells_to_win: 5,
+                p1_mark,
+                bot_mark,
+                empty_mark,
+                win_rule,
+                ..Default::default()
+            };
+            let game = Game::new(board, Level::Normal).unwrap();
+
+            let bot_move = Bot::next_move(game);
+            assert_eq!(bot_move.next == 112, win_rule == WinRule::Freestyle);
+            assert!(bot_move.next_is_valid);
+        }
+    }
+
+    fn init_opening_game(black: &[usize], white: &[usize], opening: OpeningRule) -> Game {
+        let mut cells = vec![0; 225];
+        for &idx in black.iter() {
+            cells[idx] = 1;
+        }
+        for &idx in white.iter() {
+            cells[idx] = 2;
+        }
+        let board = OpeningBoard {
+            cells,
+            cells_to_win: 5,
+            black_mark: 1,
+            white_mark: 2,
+            empty_mark: 0,
+            rows: None,
+            cols: None,
+            win_rule: WinRule::Freestyle,
+            opening,
+        };
+        Game::new(board.into(), Level::Normal).unwrap()
+    }
+
+    #[test]
+    fn bot_opening_place_three_15x15() {
+        for opening in [OpeningRule::Swap, OpeningRule::Swap2] {
+            let game = init_opening_game(&[], &[], opening);
+
+            let opening_move = Bot::opening_move(game, opening);
+            assert_eq!(opening_move.choice, OpeningChoice::PlaceThree);
+            assert_eq!(opening_move.black.len(), 2);
+            assert_eq!(opening_move.white.len(), 1);
+            assert_eq!(opening_move.black[0], 112);
+
+            let mut stones = [opening_move.black, opening_move.white].concat();
+            stones.sort_unstable();
+            stones.dedup();
+            assert_eq!(stones.len(), 3);
+            assert!(stones.iter().all(|&idx| idx < 225));
+        }
+    }
+
+    #[test]
+    fn bot_opening_swap_choose_colour_15x15() {
+        // Black stones next to each other and white far from them
+        let game = init_opening_game(&[112, 113], &[0], OpeningRule::Swap);
+        assert_eq!(
+            Bot::opening_move(game, OpeningRule::Swap).choice,
+            OpeningChoice::Black
+        );
+
+        let game = init_opening_game(&[0, 224], &[112], OpeningRule::Swap);
+        let opening_move = Bot::opening_move(game, OpeningRule::Swap);
+        assert_eq!(opening_move.choice, OpeningChoice::White);
+        assert!(opening_move.black.is_empty() && opening_move.white.is_empty());
+    }
+
+    #[test]
+    fn bot_opening_swap2_15x15() {
+        // Balanced opening gets two more stones
+        let game = init_opening_game(&[112, 125], &[113], OpeningRule::Swap2);
+        let opening_move = Bot::opening_move(game, OpeningRule::Swap2);
+
+        assert_eq!(opening_move.choice, OpeningChoice::PlaceTwo);
+        assert_eq!(opening_move.black.len(), 1);
+        assert_eq!(opening_move.white.len(), 1);
+        for idx in [opening_move.black[0], opening_move.white[0]] {
+            assert!(![112, 125, 113].contains(&idx));
+        }
+
+        // After five stones only the colour is chosen
+        let game = init_opening_game(&[112, 113, 100], &[0, 14], OpeningRule::Swap2);
+        assert_eq!(
+            Bot::opening_move(game, OpeningRule::Swap2).choice,
+            OpeningChoice::Black
+        );
+    }
+
+    #[test]
+    fn bot_threat_space_win_15x15() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        // Four in row 7 and then an open four in column 4, too deep for minimax
+        let cells = init_15x15_cells(
+            &[106, 107, 108, 124, 139],
+            &[105, 0, 14, 210, 224],
+            p1_mark,
+            bot_mark,
+        );
+        let game = init_game(&cells, p1_mark, bot_mark, empty_mark, 5);
+
+        let bot_move = Bot::next_move(game);
+        assert_eq!(bot_move.next, 109);
+        assert!(!bot_move.game_over);
+    }
+
+    #[test]
+    fn bot_threat_space_defence_15x15() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        // Four in row 7 and then an open four in column 4 for p1
+        let mut cells = init_15x15_cells(
+            &[105, 0, 14, 210],
+            &[106, 107, 108, 139, 154],
+            p1_mark,
+            bot_mark,
+        );
+        let game = init_game(&cells, p1_mark, bot_mark, empty_mark, 5);
+
+        let bot_move = Bot::next_move(game);
+        assert!(bot_move.next_is_valid);
+
+        cells[bot_move.next as usize] = bot_mark;
+        let mut game = init_game(&cells, p1_mark, bot_mark, empty_mark, 5);
+
+        let p1_win = ThreatSearch::default().winning_move(&mut game, p1_mark, ThreatMode::Vcf);
+        assert_eq!(p1_win, None);
+    }
+
+    #[test]
+    fn bot_game_final_move() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let cells_to_win = 3;
+
+        let cells_collections: [[i8; 9]; 3] = [
+            [-1, 1, -1, -1, 1, 1, 1, -1, 0],
+            [1, 1, 0, -1, -1, 1, 1, -1, -1],
+            [1, -1, 0, 0, 1, -1, 0, 0, 0],
+        ];
+        let correct_next_move: [u16; 3] = [8, 2, 8];
+        let correct_winner: [i8; 3] = [empty_mark, bot_mark, bot_mark];
+
+        let it = cells_collections.iter().zip(0..correct_next_move.len());
+
+        for (cells, i) in it {
+            let game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+
+            let bot_move = Bot::next_move(game);
+
+            assert_eq!(bot_move.next, correct_next_move[i]);
+            assert_eq!(bot_move.winner, correct_winner[i]);
+            assert!(bot_move.next_is_valid);
+            assert!(bot_move.game_over);
+        }
+    }
+
+    #[test]
+    fn bot_game_final_move_markers_correct() {
+        let (p1_mark, bot_mark, empty_mark) = (5, -3, 2);
+        let cells_to_win = 3;
+
+        let cells_collections: [[i8; 9]; 2] = [
+            [5, 5, -3, 2, -3, 2, 2, 2, 2],
+            [5, -3, -3, -3, -3, 5, 5, 5, 2],
+        ];
+
+        let correct_next_move: [u16; 2] = [6, 8];
+        let correct_winner: [i8; 2] = [bot_mark, empty_mark];
+
+        let it = cells_collections.iter().zip(0..correct_next_move.len());
+
+        for (cells, i) in it {
+            let game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+
+            let bot_move = Bot::next_move(game);
+
+            // Check that player markers are the original and not the normalized
+            assert_eq!(bot_move.next, correct_next_move[i]);
+            assert_eq!(bot_move.winner, correct_winner[i]);
+            assert!(bot_move.next_is_valid);
+            assert!(bot_move.game_over);
+        }
+    }
+
+    #[test]
+    fn bot_not_game_final_move() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let cells_to_win = 3;
+
+        let cells_collections: [[i8; 9]; 3] = [
+            [1, 0, -1, -1, 1, 0, 1, 0, -1],
+            [1, -1, 1, 0, 1, 0, -1, 0, -1],
+            [-1, 0, 0, 0, 0, 0, 1, 0, -1],
+        ];
+        let correct_next_move: [u16; 3] = [5, 7, 4];
+
+        let it = cells_collections.iter().zip(0..correct_next_move.len());
+
+        for (cells, i) in it {
+            let game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+
+            let bot_move = Bot::next_move(game);
+
+            assert_eq!(bot_move.next, correct_next_move[i]);
+            assert_eq!(bot_move.winner, empty_mark);
+            assert!(bot_move.next_is_valid);
+            assert!(!bot_move.game_over);
+        }
+    }
+
+    #[test]
+    fn bot_game_already_over() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        let cells_to_win = 4;
+
+        let cells_collections: [[i8; 25]; 3] = [
+            [
+                0, 1, 1, 0, 0, 0, -1, -1, -1, -1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            [
+                0, 0, 1, -1, -1, 0, 0, 1, -1, 0, 0, 0, 1, -1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0,
             ],
             [
                 -1, 1, 1, -1, -1, -1, 1, -1, 1, 1, -1, 1, 1, -1, -1, 1, -1, 1, -1, 1, -1, 1, -1, 1,
@@ -549,8 +2154,8 @@ mod tests {
             let bot_move = Bot::next_move(game);
 
             assert_eq!(bot_move.winner, correct_winner[i]);
-            assert_eq!(bot_move.game_over, true);
-            assert_eq!(bot_move.next_is_valid, false);
+            assert!(bot_move.game_over);
+            assert!(!bot_move.next_is_valid);
         }
     }
 
@@ -559,6 +2164,7 @@ mod tests {
         cells_to_win: u8,
         play_p1_first_move: bool,
         p1_first_move_idx: usize,
+        misere: bool,
     ) {
         let empty_mark = 0;
         let (mut p1_mark, mut bot_mark) = (-1, 1);
@@ -578,6 +2184,8 @@ mod tests {
                 p1_mark,
                 bot_mark,
                 empty_mark,
+                misere,
+                ..Default::default()
             };
             let game = match Game::new(board, Level::Normal) {
                 Ok(game) => game,
@@ -627,88 +2235,98 @@ mod tests {
 
     #[test]
     fn complete_game_play_3x3_3inarow_normal() {
-        play_complete_game(9, 3, false, 0);
+        play_complete_game(9, 3, false, 0, false);
     }
 
     #[test]
     fn complete_game_play_4x4_4inarow_normal() {
-        play_complete_game(16, 4, false, 0);
+        play_complete_game(16, 4, false, 0, false);
+    }
+
+    #[test]
+    fn complete_game_play_3x3_3inarow_misere() {
+        play_complete_game(9, 3, false, 0, true);
+    }
+
+    #[test]
+    fn complete_game_play_4x4_4inarow_misere() {
+        play_complete_game(16, 4, false, 0, true);
     }
 
     #[test]
     fn complete_game_play_5x5_4inarow_normal() {
         // Run separately with live board updates: cargo test --bin server complete_game_play_5x5_4inarow_normal -- --nocapture
-        play_complete_game(25, 4, false, 0);
+        play_complete_game(25, 4, false, 0, false);
     }
 
     #[test]
     #[ignore]
     fn complete_game_play_5x5_4inarow_after_p1_center_move() {
-        play_complete_game(25, 4, true, 12);
+        play_complete_game(25, 4, true, 12, false);
     }
 
     #[test]
     #[ignore]
     fn complete_game_play_5x5_4inarow_after_p1_lower_row_move() {
         // Run if there are doubts about the bot's answer to p1 opening move
-        play_complete_game(25, 4, true, 21);
+        play_complete_game(25, 4, true, 21, false);
     }
 
     #[test]
     fn complete_game_play_6x6_5inarow_normal() {
-        play_complete_game(36, 5, false, 0);
+        play_complete_game(36, 5, false, 0, false);
     }
 
     #[test]
     fn complete_game_play_7x7_5inarow_normal() {
-        play_complete_game(49, 5, false, 0);
+        play_complete_game(49, 5, false, 0, false);
     }
 
     #[test]
     fn complete_game_play_8x8_5inarow_normal() {
-        play_complete_game(64, 5, false, 0);
+        play_complete_game(64, 5, false, 0, false);
     }
 
     #[test]
     fn complete_game_play_9x9_5inarow_normal() {
-        play_complete_game(81, 5, false, 0);
+        play_complete_game(81, 5, false, 0, false);
     }
 
     #[test]
     #[ignore]
     fn complete_game_play_10x10_5inarow_normal() {
         // How to run: cargo test --bin server complete_game_play_10x10_5inarow_normal -- --nocapture --ignored
-        play_complete_game(100, 5, false, 0);
+        play_complete_game(100, 5, false, 0, false);
     }
 
     #[test]
     #[ignore]
     fn complete_game_play_11x11_5inarow_normal() {
-        play_complete_game(11 * 11, 5, false, 0);
+        play_complete_game(11 * 11, 5, false, 0, false);
     }
 
     #[test]
     #[ignore]
     fn complete_game_play_12x12_5inarow_normal() {
-        play_complete_game(12 * 12, 5, false, 0);
+        play_complete_game(12 * 12, 5, false, 0, false);
     }
 
     #[test]
     #[ignore]
     fn complete_game_play_13x13_5inarow_normal() {
-        play_complete_game(13 * 13, 5, false, 0);
+        play_complete_game(13 * 13, 5, false, 0, false);
     }
 
     #[test]
     #[ignore]
     fn complete_game_play_14x14_5inarow_normal() {
-        play_complete_game(14 * 14, 5, false, 0);
+        play_complete_game(14 * 14, 5, false, 0, false);
     }
 
     #[test]
     #[ignore]
     fn complete_game_play_15x15_5inarow_normal() {
         // Starting player should win this game
-        play_complete_game(15 * 15, 5, false, 0);
+        play_complete_game(15 * 15, 5, false, 0, false);
     }
 }
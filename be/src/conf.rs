@@ -1,101 +1,88 @@
-use crate::models::{Board, Level};
+use serde::Deserialize;
+use std::cmp;
 
-#[derive(Clone, Copy, Debug)]
-pub enum BoardSize {
-    X33 = 3,
-    X44 = 4,
-    X55 = 5,
-    X66 = 6,
-    X77 = 7,
-    X88 = 8,
-    X99 = 9,
-    X1010 = 10,
-    X1111 = 11,
-    X1212 = 12,
-    X1313 = 13,
-    X1414 = 14,
-    X1515 = 15,
-}
+use crate::{
+    bitboard::MAX_WINDOW_CELLS,
+    models::{Board, Level, WinRule},
+};
+
+// Do not define board sizes over this value (19x19), bitboards have room for it
+const MAX_BOARD_SIZE: usize = 361;
+
+// Smallest row and column count and cells to win value accepted to a board table
+const MIN_BOARD_SIDE: u8 = 3;
+const MIN_CELLS_TO_WIN: u8 = 3;
 
-// Do not define board sizes over this value
-const MAX_BOARD_SIZE: u8 = u8::MAX;
-
-const BOARD_SIZES: [(u8, BoardSize); 13] = [
-    (9, BoardSize::X33),
-    (16, BoardSize::X44),
-    (25, BoardSize::X55),
-    (36, BoardSize::X66),
-    (49, BoardSize::X77),
-    (64, BoardSize::X88),
-    (81, BoardSize::X99),
-    (100, BoardSize::X1010),
-    (121, BoardSize::X1111),
-    (144, BoardSize::X1212),
-    (169, BoardSize::X1313),
-    (196, BoardSize::X1414),
-    (225, BoardSize::X1515),
+// Do not define cells to win over this value, longer windows overflow the bitboard counters
+const MAX_CELLS_TO_WIN: u8 = MAX_WINDOW_CELLS as u8;
+
+// Rows, columns, cells to win min and max, max depth for easy and normal levels.
+// Cells to win max must be less than or equal to both rows and columns.
+const DEFAULT_BOARD_CONFS: [BoardConf; 21] = [
+    BoardConf::new(3, 3, 3, 3, 1, 9),
+    BoardConf::new(4, 4, 4, 4, 2, 7),
+    BoardConf::new(5, 5, 4, 5, 3, 6),
+    BoardConf::new(6, 6, 4, 6, 3, 5),
+    BoardConf::new(6, 7, 4, 6, 3, 4),
+    BoardConf::new(7, 6, 4, 6, 3, 4),
+    BoardConf::new(7, 7, 4, 7, 3, 4),
+    BoardConf::new(8, 8, 4, 8, 3, 4),
+    BoardConf::new(9, 9, 4, 9, 3, 4),
+    BoardConf::new(10, 10, 5, 10, 2, 3),
+    BoardConf::new(10, 15, 5, 10, 2, 3),
+    BoardConf::new(11, 11, 5, 10, 2, 3),
+    BoardConf::new(12, 12, 5, 10, 2, 3),
+    BoardConf::new(13, 13, 5, 10, 2, 3),
+    BoardConf::new(14, 14, 5, 10, 2, 3),
+    BoardConf::new(15, 10, 5, 10, 2, 3),
+    BoardConf::new(15, 15, 5, 10, 2, 3),
+    BoardConf::new(16, 16, 5, 10, 2, 3),
+    BoardConf::new(17, 17, 5, 10, 2, 3),
+    BoardConf::new(18, 18, 5, 10, 2, 3),
+    BoardConf::new(19, 19, 5, 10, 2, 3),
 ];
 
-pub const X33_CELLS_TO_WIN_MIN: u8 = 3;
-pub const X44_CELLS_TO_WIN_MIN: u8 = 4;
-pub const X55_CELLS_TO_WIN_MIN: u8 = 4;
-pub const X66_CELLS_TO_WIN_MIN: u8 = 4;
-pub const X77_CELLS_TO_WIN_MIN: u8 = 4;
-pub const X88_CELLS_TO_WIN_MIN: u8 = 4;
-pub const X99_CELLS_TO_WIN_MIN: u8 = 4;
-pub const X1010_CELLS_TO_WIN_MIN: u8 = 5;
-pub const X1111_CELLS_TO_WIN_MIN: u8 = 5;
-pub const X1212_CELLS_TO_WIN_MIN: u8 = 5;
-pub const X1313_CELLS_TO_WIN_MIN: u8 = 5;
-pub const X1414_CELLS_TO_WIN_MIN: u8 = 5;
-pub const X1515_CELLS_TO_WIN_MIN: u8 = 5;
-// Max value must be less than or equal to offset (for a k x k board, offset is k)
-pub const X33_CELLS_TO_WIN_MAX: u8 = 3;
-pub const X44_CELLS_TO_WIN_MAX: u8 = 4;
-pub const X55_CELLS_TO_WIN_MAX: u8 = 5;
-pub const X66_CELLS_TO_WIN_MAX: u8 = 6;
-pub const X77_CELLS_TO_WIN_MAX: u8 = 7;
-pub const X88_CELLS_TO_WIN_MAX: u8 = 8;
-pub const X99_CELLS_TO_WIN_MAX: u8 = 9;
-pub const X1010_CELLS_TO_WIN_MAX: u8 = 10;
-pub const X1111_CELLS_TO_WIN_MAX: u8 = 10;
-pub const X1212_CELLS_TO_WIN_MAX: u8 = 10;
-pub const X1313_CELLS_TO_WIN_MAX: u8 = 10;
-pub const X1414_CELLS_TO_WIN_MAX: u8 = 10;
-pub const X1515_CELLS_TO_WIN_MAX: u8 = 10;
-
-const X33_MAX_DEPTH: u8 = 9;
-const X44_MAX_DEPTH: u8 = 7;
-const X55_MAX_DEPTH: u8 = 6;
-const X66_MAX_DEPTH: u8 = 5;
-const X77_MAX_DEPTH: u8 = 4;
-const X88_MAX_DEPTH: u8 = 4;
-const X99_MAX_DEPTH: u8 = 4;
-const X1010_MAX_DEPTH: u8 = 3;
-const X1111_MAX_DEPTH: u8 = 3;
-const X1212_MAX_DEPTH: u8 = 3;
-const X1313_MAX_DEPTH: u8 = 3;
-const X1414_MAX_DEPTH: u8 = 3;
-const X1515_MAX_DEPTH: u8 = 3;
-
-const X33_EASY_MAX_DEPTH: u8 = 1;
-const X44_EASY_MAX_DEPTH: u8 = 2;
-const X55_EASY_MAX_DEPTH: u8 = 3;
-const X66_EASY_MAX_DEPTH: u8 = 3;
-const X77_EASY_MAX_DEPTH: u8 = 3;
-const X88_EASY_MAX_DEPTH: u8 = 3;
-const X99_EASY_MAX_DEPTH: u8 = 3;
-const X1010_EASY_MAX_DEPTH: u8 = 2;
-const X1111_EASY_MAX_DEPTH: u8 = 2;
-const X1212_EASY_MAX_DEPTH: u8 = 2;
-const X1313_EASY_MAX_DEPTH: u8 = 2;
-const X1414_EASY_MAX_DEPTH: u8 = 2;
-const X1515_EASY_MAX_DEPTH: u8 = 2;
+/// Allowed cells to win values and max search depths of the rows x cols board.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct BoardConf {
+    pub rows: u8,
+    pub cols: u8,
+    pub cells_to_win_min: u8,
+    pub cells_to_win_max: u8,
+    pub easy_max_depth: u8,
+    pub normal_max_depth: u8,
+}
+
+/// Table of the accepted board sizes.
+///
+/// Default table can be replaced at server startup by a JSON array of
+/// `BoardConf` objects, which is validated before use.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(transparent)]
+pub struct BoardTable {
+    boards: Vec<BoardConf>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BoardTableError {
+    Parse(String),
+    Empty,
+    Size(u8, u8),
+    DuplicateSize(u8, u8),
+    CellsToWin(u8, u8),
+    Depth(u8, u8),
+}
+
+// Captured pairs winning a game with captures
+pub const CAPTURES_TO_WIN: u8 = 5;
+
+// Iterations of the Monte Carlo tree search by default
+pub const MCTS_ITERATIONS: u32 = 2000;
 
 #[derive(Debug)]
 pub struct BoardParams {
-    pub size: BoardSize,
-    pub offset: usize,
+    pub rows: usize,
+    pub cols: usize,
     pub to_win: usize,
     pub max_depth: usize,
 }
@@ -106,223 +93,153 @@ pub enum GameInitError {
     Marks,
     Inconsistent,
     CellsToWin,
+    Forbidden,
+}
+
+impl BoardConf {
+    pub const fn new(
+        rows: u8,
+        cols: u8,
+        cells_to_win_min: u8,
+        cells_to_win_max: u8,
+        easy_max_depth: u8,
+        normal_max_depth: u8,
+    ) -> Self {
+        BoardConf {
+            rows,
+            cols,
+            cells_to_win_min,
+            cells_to_win_max,
+            easy_max_depth,
+            normal_max_depth,
+        }
+    }
+
+    pub fn max_depth(&self, level: Level) -> u8 {
+        match level {
+            Level::Easy => self.easy_max_depth,
+            Level::Normal => self.normal_max_depth,
+        }
+    }
+
+    fn validate(&self) -> Result<(), BoardTableError> {
+        let (rows, cols) = (self.rows, self.cols);
+
+        if cmp::min(rows, cols) < MIN_BOARD_SIDE || self.cells_count() > MAX_BOARD_SIZE {
+            return Err(BoardTableError::Size(rows, cols));
+        }
+        if self.cells_to_win_min < MIN_CELLS_TO_WIN
+            || self.cells_to_win_min > self.cells_to_win_max
+            || self.cells_to_win_max > cmp::min(rows, cols)
+            || self.cells_to_win_max > MAX_CELLS_TO_WIN
+        {
+            return Err(BoardTableError::CellsToWin(rows, cols));
+        }
+        if self.easy_max_depth == 0 || self.normal_max_depth == 0 {
+            return Err(BoardTableError::Depth(rows, cols));
+        }
+        Ok(())
+    }
+
+    fn cells_count(&self) -> usize {
+        self.rows as usize * self.cols as usize
+    }
+}
+
+impl Default for BoardTable {
+    fn default() -> Self {
+        BoardTable {
+            boards: DEFAULT_BOARD_CONFS.to_vec(),
+        }
+    }
+}
+
+impl BoardTable {
+    /// Parse and validate a board table given as a JSON array.
+    pub fn from_json(json: &str) -> Result<Self, BoardTableError> {
+        let table: BoardTable = serde_json::from_str(json)
+            .map_err(|error| BoardTableError::Parse(error.to_string()))?;
+        table.validate()?;
+
+        Ok(table)
+    }
+
+    pub fn validate(&self) -> Result<(), BoardTableError> {
+        if self.boards.is_empty() {
+            return Err(BoardTableError::Empty);
+        }
+
+        for (idx, board_conf) in self.boards.iter().enumerate() {
+            board_conf.validate()?;
+
+            if self.boards[..idx]
+                .iter()
+                .any(|other| (other.rows, other.cols) == (board_conf.rows, board_conf.cols))
+            {
+                return Err(BoardTableError::DuplicateSize(
+                    board_conf.rows,
+                    board_conf.cols,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Conf of the rows x cols board, if such board is accepted.
+    pub fn board_conf(&self, rows: usize, cols: usize) -> Option<&BoardConf> {
+        self.boards
+            .iter()
+            .find(|board_conf| (board_conf.rows as usize, board_conf.cols as usize) == (rows, cols))
+    }
 }
 
 impl BoardParams {
-    pub fn new(board: &Board, level: Level) -> Result<Self, GameInitError> {
+    pub fn new(
+        board: &Board,
+        level: Level,
+        board_table: &BoardTable,
+    ) -> Result<Self, GameInitError> {
         Self::board_inconsistent(board)?;
 
-        if board.cells.len() > MAX_BOARD_SIZE as usize {
+        if board.cells.len() > MAX_BOARD_SIZE {
             return Err(GameInitError::Size);
         }
 
-        let cells_total_count = board.cells.len() as u8;
+        let (rows, cols) = Self::board_dimensions(board).ok_or(GameInitError::Size)?;
 
-        for &(cells_count, board_size) in BOARD_SIZES.iter() {
-            if cells_total_count == cells_count {
-                if let Ok(board_params) = Self::init(board_size, level, board.cells_to_win) {
-                    return Ok(board_params);
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
+        match board_table.board_conf(rows, cols) {
+            Some(board_conf) => Self::init(board_conf, level, board.cells_to_win),
+            None => Err(GameInitError::Size),
         }
-        Err(GameInitError::Size)
     }
 
-    fn init(board_size: BoardSize, level: Level, cells_to_win: u8) -> Result<Self, GameInitError> {
-        let offset = board_size as usize;
+    /// Rows and columns of the board, a square board if they are not given.
+    /// Boards without cells have no dimensions.
+    fn board_dimensions(board: &Board) -> Option<(usize, usize)> {
+        let cells_count = board.cells.len();
 
-        let (to_win, max_depth) = match (board_size, level) {
-            (BoardSize::X33, Level::Easy) => {
-                if (X33_CELLS_TO_WIN_MIN..=X33_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X33_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X33, Level::Normal) => {
-                if (X33_CELLS_TO_WIN_MIN..=X33_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X33_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X44, Level::Easy) => {
-                if (X44_CELLS_TO_WIN_MIN..=X44_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X44_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X44, Level::Normal) => {
-                if (X44_CELLS_TO_WIN_MIN..=X44_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X44_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X55, Level::Easy) => {
-                if (X55_CELLS_TO_WIN_MIN..=X55_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X55_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X55, Level::Normal) => {
-                if (X55_CELLS_TO_WIN_MIN..=X55_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X55_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X66, Level::Easy) => {
-                if (X66_CELLS_TO_WIN_MIN..=X66_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X66_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X66, Level::Normal) => {
-                if (X66_CELLS_TO_WIN_MIN..=X66_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X66_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X77, Level::Easy) => {
-                if (X77_CELLS_TO_WIN_MIN..=X77_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X77_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X77, Level::Normal) => {
-                if (X77_CELLS_TO_WIN_MIN..=X77_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X77_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X88, Level::Easy) => {
-                if (X88_CELLS_TO_WIN_MIN..=X88_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X88_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X88, Level::Normal) => {
-                if (X88_CELLS_TO_WIN_MIN..=X88_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X88_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X99, Level::Easy) => {
-                if (X99_CELLS_TO_WIN_MIN..=X99_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X99_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X99, Level::Normal) => {
-                if (X99_CELLS_TO_WIN_MIN..=X99_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X99_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1010, Level::Easy) => {
-                if (X1010_CELLS_TO_WIN_MIN..=X1010_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1010_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1010, Level::Normal) => {
-                if (X1010_CELLS_TO_WIN_MIN..=X1010_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1010_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1111, Level::Easy) => {
-                if (X1111_CELLS_TO_WIN_MIN..=X1111_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1111_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1111, Level::Normal) => {
-                if (X1111_CELLS_TO_WIN_MIN..=X1111_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1111_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1212, Level::Easy) => {
-                if (X1212_CELLS_TO_WIN_MIN..=X1212_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1212_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1212, Level::Normal) => {
-                if (X1212_CELLS_TO_WIN_MIN..=X1212_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1212_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1313, Level::Easy) => {
-                if (X1313_CELLS_TO_WIN_MIN..=X1313_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1313_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1313, Level::Normal) => {
-                if (X1313_CELLS_TO_WIN_MIN..=X1313_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1313_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1414, Level::Easy) => {
-                if (X1414_CELLS_TO_WIN_MIN..=X1414_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1414_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1414, Level::Normal) => {
-                if (X1414_CELLS_TO_WIN_MIN..=X1414_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1414_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1515, Level::Easy) => {
-                if (X1515_CELLS_TO_WIN_MIN..=X1515_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1515_EASY_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
-            }
-            (BoardSize::X1515, Level::Normal) => {
-                if (X1515_CELLS_TO_WIN_MIN..=X1515_CELLS_TO_WIN_MAX).contains(&cells_to_win) {
-                    (cells_to_win, X1515_MAX_DEPTH)
-                } else {
-                    return Err(GameInitError::CellsToWin);
-                }
+        let (rows, cols) = match (board.rows, board.cols) {
+            (Some(rows), Some(cols)) => (rows as usize, cols as usize),
+            (None, None) => {
+                let side = cells_count.isqrt();
+                (side, side)
             }
+            _ => return None,
         };
 
+        (rows > 0 && cols > 0 && rows * cols == cells_count).then_some((rows, cols))
+    }
+
+    fn init(board_conf: &BoardConf, level: Level, cells_to_win: u8) -> Result<Self, GameInitError> {
+        if !(board_conf.cells_to_win_min..=board_conf.cells_to_win_max).contains(&cells_to_win) {
+            return Err(GameInitError::CellsToWin);
+        }
+
         Ok(BoardParams {
-            size: board_size,
-            offset,
-            to_win: to_win as usize,
-            max_depth: max_depth as usize,
+            rows: board_conf.rows as usize,
+            cols: board_conf.cols as usize,
+            to_win: cells_to_win as usize,
+            max_depth: board_conf.max_depth(level) as usize,
         })
     }
 
@@ -334,8 +251,18 @@ impl BoardParams {
             return Err(GameInitError::Marks);
         }
 
+        if board.blocked_mark.is_some_and(|blocked_mark| {
+            [board.p1_mark, board.bot_mark, board.empty_mark].contains(&blocked_mark)
+        }) {
+            return Err(GameInitError::Marks);
+        }
+
+        // Blocked cells are neutral and not counted for either player
         if board.cells.iter().any(|&cell| {
-            cell != board.p1_mark && cell != board.bot_mark && cell != board.empty_mark
+            cell != board.p1_mark
+                && cell != board.bot_mark
+                && cell != board.empty_mark
+                && Some(cell) != board.blocked_mark
         }) {
             return Err(GameInitError::Marks);
         }
@@ -352,10 +279,60 @@ impl BoardParams {
             .filter(|&cell| *cell == board.bot_mark)
             .count();
 
-        if p1_marks > bot_marks + 1 || bot_marks > p1_marks {
+        let captured_pairs = [board.p1_captured_pairs, board.bot_captured_pairs];
+        if captured_pairs
+            .iter()
+            .any(|&pairs| pairs > CAPTURES_TO_WIN || (pairs > 0 && !board.captures))
+        {
+            return Err(GameInitError::Inconsistent);
+        }
+        // Capture wins have no misère counterpart
+        if board.misere && board.captures {
             return Err(GameInitError::Inconsistent);
         }
 
+        // Markers placed during the game, including the captured ones
+        let p1_marks = p1_marks + 2 * board.bot_captured_pairs as usize;
+        let bot_marks = bot_marks + 2 * board.p1_captured_pairs as usize;
+
+        // In Connect6 the turn changes after the first marker and then after every two markers
+        let marks_consistent = if board.connect6 {
+            p1_marks == bot_marks + 1 || p1_marks + bot_marks == 0
+        } else {
+            p1_marks <= bot_marks + 1 && bot_marks <= p1_marks
+        };
+
+        if !marks_consistent {
+            return Err(GameInitError::Inconsistent);
+        }
+
+        // Latest move of p1 must be known when it must be checked for renju restrictions
+        let p1_black = if board.connect6 {
+            p1_marks % 2 == 1
+        } else {
+            p1_marks > bot_marks
+        };
+        let last_move_required = board.win_rule == WinRule::Renju && p1_black;
+
+        match board.last_move {
+            Some(index) if board.cells.get(index as usize) != Some(&board.p1_mark) => {
+                return Err(GameInitError::Inconsistent);
+            }
+            None if last_move_required => return Err(GameInitError::Inconsistent),
+            _ => (),
+        }
+
+        // With gravity every stone must lie on the bottom row or on another stone.
+        // Boards of unaccepted dimensions are left for the size checks.
+        if let (true, Some((_, cols))) = (board.gravity, Self::board_dimensions(board)) {
+            let floating = (0..board.cells.len() - cols).any(|idx| {
+                board.cells[idx] != board.empty_mark && board.cells[idx + cols] == board.empty_mark
+            });
+            if floating {
+                return Err(GameInitError::Inconsistent);
+            }
+        }
+
         Ok(())
     }
 }
@@ -366,210 +343,145 @@ mod tests {
 
     #[test]
     fn board_params_valid_init_calls() {
-        let test_cases = vec![
-            (
-                BoardSize::X33,
-                Level::Easy,
-                X33_CELLS_TO_WIN_MIN,
-                X33_CELLS_TO_WIN_MAX,
-                X33_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X33,
-                Level::Normal,
-                X33_CELLS_TO_WIN_MIN,
-                X33_CELLS_TO_WIN_MAX,
-                X33_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X44,
-                Level::Easy,
-                X44_CELLS_TO_WIN_MIN,
-                X44_CELLS_TO_WIN_MAX,
-                X44_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X44,
-                Level::Normal,
-                X44_CELLS_TO_WIN_MIN,
-                X44_CELLS_TO_WIN_MAX,
-                X44_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X55,
-                Level::Easy,
-                X55_CELLS_TO_WIN_MIN,
-                X55_CELLS_TO_WIN_MAX,
-                X55_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X55,
-                Level::Normal,
-                X55_CELLS_TO_WIN_MIN,
-                X55_CELLS_TO_WIN_MAX,
-                X55_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X66,
-                Level::Easy,
-                X66_CELLS_TO_WIN_MIN,
-                X66_CELLS_TO_WIN_MAX,
-                X66_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X66,
-                Level::Normal,
-                X66_CELLS_TO_WIN_MIN,
-                X66_CELLS_TO_WIN_MAX,
-                X66_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X77,
-                Level::Easy,
-                X77_CELLS_TO_WIN_MIN,
-                X77_CELLS_TO_WIN_MAX,
-                X77_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X77,
-                Level::Normal,
-                X77_CELLS_TO_WIN_MIN,
-                X77_CELLS_TO_WIN_MAX,
-                X77_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X88,
-                Level::Easy,
-                X88_CELLS_TO_WIN_MIN,
-                X88_CELLS_TO_WIN_MAX,
-                X88_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X88,
-                Level::Normal,
-                X88_CELLS_TO_WIN_MIN,
-                X88_CELLS_TO_WIN_MAX,
-                X88_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X99,
-                Level::Easy,
-                X99_CELLS_TO_WIN_MIN,
-                X99_CELLS_TO_WIN_MAX,
-                X99_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X99,
-                Level::Normal,
-                X99_CELLS_TO_WIN_MIN,
-                X99_CELLS_TO_WIN_MAX,
-                X99_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X1010,
-                Level::Easy,
-                X1010_CELLS_TO_WIN_MIN,
-                X1010_CELLS_TO_WIN_MAX,
-                X1010_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X1010,
-                Level::Normal,
-                X1010_CELLS_TO_WIN_MIN,
-                X1010_CELLS_TO_WIN_MAX,
-                X1010_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X1111,
-                Level::Easy,
-                X1111_CELLS_TO_WIN_MIN,
-                X1111_CELLS_TO_WIN_MAX,
-                X1111_EASY_MAX_DEPTH,
-            ),
-            (
-                BoardSize::X1111,
-                Level::Normal,
-                X1111_CELLS_TO_WIN_MIN,
-                X1111_CELLS_TO_WIN_MAX,
-                X1111_MAX_DEPTH,
-            ),
+        for board_conf in DEFAULT_BOARD_CONFS.iter() {
+            for level in [Level::Easy, Level::Normal] {
+                for cells_to_win in board_conf.cells_to_win_min..=board_conf.cells_to_win_max {
+                    let result = BoardParams::init(board_conf, level, cells_to_win).unwrap();
+                    assert_eq!(result.rows, board_conf.rows as usize);
+                    assert_eq!(result.cols, board_conf.cols as usize);
+                    assert_eq!(result.to_win, cells_to_win as usize);
+                    assert_eq!(result.max_depth, board_conf.max_depth(level) as usize);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn board_params_invalid_init_calls() {
+        let table = BoardTable::default();
+
+        for (rows, cols) in [(3, 3), (19, 19)] {
+            let board_conf = table.board_conf(rows, cols).unwrap();
+
+            for level in [Level::Easy, Level::Normal] {
+                BoardParams::init(board_conf, level, board_conf.cells_to_win_min - 1).unwrap_err();
+                BoardParams::init(board_conf, level, board_conf.cells_to_win_max + 1).unwrap_err();
+            }
+        }
+        assert!(table.board_conf(3, 4).is_none());
+    }
+
+    #[test]
+    fn board_params_empty_gravity_board() {
+        for (rows, cols) in [(Some(0), Some(7)), (Some(6), Some(0)), (None, None)] {
+            let board = Board {
+                cells: Vec::new(),
+                cells_to_win: 4,
+                p1_mark: -1,
+                bot_mark: 1,
+                empty_mark: 0,
+                rows,
+                cols,
+                gravity: true,
+                ..Default::default()
+            };
+            let result = BoardParams::new(&board, Level::Normal, &BoardTable::default());
+            assert!(
+                matches!(result, Err(GameInitError::Size)),
+                "{:?}",
+                (rows, cols)
+            );
+        }
+    }
+
+    #[test]
+    fn board_params_misere_with_captures() {
+        let board = |captures| Board {
+            cells: vec![0; 25],
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            misere: true,
+            captures,
+            ..Default::default()
+        };
+        let table = BoardTable::default();
+
+        assert!(BoardParams::new(&board(false), Level::Normal, &table).is_ok());
+        assert!(matches!(
+            BoardParams::new(&board(true), Level::Normal, &table),
+            Err(GameInitError::Inconsistent)
+        ));
+    }
+
+    #[test]
+    fn default_board_table_is_valid() {
+        assert_eq!(BoardTable::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn board_table_from_json() {
+        let json = r#"[
+            {"rows": 3, "cols": 3, "cells_to_win_min": 3, "cells_to_win_max": 3, "easy_max_depth": 1, "normal_max_depth": 9},
+            {"rows": 6, "cols": 7, "cells_to_win_min": 4, "cells_to_win_max": 5, "easy_max_depth": 2, "normal_max_depth": 5}
+        ]"#;
+        let table = BoardTable::from_json(json).unwrap();
+
+        assert_eq!(
+            table.board_conf(6, 7),
+            Some(&BoardConf::new(6, 7, 4, 5, 2, 5))
+        );
+        assert!(table.board_conf(7, 6).is_none());
+    }
+
+    #[test]
+    fn board_table_invalid() {
+        let test_cases = [
+            (vec![], BoardTableError::Empty),
             (
-                BoardSize::X1212,
-                Level::Easy,
-                X1212_CELLS_TO_WIN_MIN,
-                X1212_CELLS_TO_WIN_MAX,
-                X1212_EASY_MAX_DEPTH,
+                vec![BoardConf::new(2, 3, 3, 3, 1, 1)],
+                BoardTableError::Size(2, 3),
             ),
             (
-                BoardSize::X1212,
-                Level::Normal,
-                X1212_CELLS_TO_WIN_MIN,
-                X1212_CELLS_TO_WIN_MAX,
-                X1212_MAX_DEPTH,
+                vec![BoardConf::new(19, 20, 5, 5, 1, 1)],
+                BoardTableError::Size(19, 20),
             ),
             (
-                BoardSize::X1313,
-                Level::Easy,
-                X1313_CELLS_TO_WIN_MIN,
-                X1313_CELLS_TO_WIN_MAX,
-                X1313_EASY_MAX_DEPTH,
+                vec![BoardConf::new(5, 5, 2, 5, 1, 1)],
+                BoardTableError::CellsToWin(5, 5),
             ),
             (
-                BoardSize::X1313,
-                Level::Normal,
-                X1313_CELLS_TO_WIN_MIN,
-                X1313_CELLS_TO_WIN_MAX,
-                X1313_MAX_DEPTH,
+                vec![BoardConf::new(5, 5, 5, 4, 1, 1)],
+                BoardTableError::CellsToWin(5, 5),
             ),
             (
-                BoardSize::X1414,
-                Level::Easy,
-                X1414_CELLS_TO_WIN_MIN,
-                X1414_CELLS_TO_WIN_MAX,
-                X1414_EASY_MAX_DEPTH,
+                vec![BoardConf::new(5, 6, 4, 6, 1, 1)],
+                BoardTableError::CellsToWin(5, 6),
             ),
             (
-                BoardSize::X1414,
-                Level::Normal,
-                X1414_CELLS_TO_WIN_MIN,
-                X1414_CELLS_TO_WIN_MAX,
-                X1414_MAX_DEPTH,
+                vec![BoardConf::new(19, 19, 5, 16, 1, 1)],
+                BoardTableError::CellsToWin(19, 19),
             ),
             (
-                BoardSize::X1515,
-                Level::Easy,
-                X1515_CELLS_TO_WIN_MIN,
-                X1515_CELLS_TO_WIN_MAX,
-                X1515_EASY_MAX_DEPTH,
+                vec![BoardConf::new(5, 5, 4, 5, 0, 1)],
+                BoardTableError::Depth(5, 5),
             ),
             (
-                BoardSize::X1515,
-                Level::Normal,
-                X1515_CELLS_TO_WIN_MIN,
-                X1515_CELLS_TO_WIN_MAX,
-                X1515_MAX_DEPTH,
+                vec![
+                    BoardConf::new(5, 6, 4, 5, 1, 1),
+                    BoardConf::new(5, 6, 4, 4, 1, 1),
+                ],
+                BoardTableError::DuplicateSize(5, 6),
             ),
         ];
-        for (board_size, level, cells_to_win_min, cells_to_win_max, max_depth) in test_cases {
-            for cells_to_win in cells_to_win_min..=cells_to_win_max {
-                let result = BoardParams::init(board_size, level, cells_to_win).unwrap();
-                assert_eq!(result.offset, board_size as usize);
-                assert_eq!(result.to_win, cells_to_win as usize);
-                assert_eq!(result.max_depth, max_depth as usize);
-            }
-        }
-    }
 
-    #[test]
-    fn board_params_invalid_init_calls() {
-        BoardParams::init(BoardSize::X33, Level::Easy, X33_CELLS_TO_WIN_MIN - 1).unwrap_err();
-        BoardParams::init(BoardSize::X33, Level::Easy, X33_CELLS_TO_WIN_MAX + 1).unwrap_err();
-        BoardParams::init(BoardSize::X33, Level::Normal, X33_CELLS_TO_WIN_MIN - 1).unwrap_err();
-        BoardParams::init(BoardSize::X33, Level::Normal, X33_CELLS_TO_WIN_MAX + 1).unwrap_err();
-
-        BoardParams::init(BoardSize::X1515, Level::Easy, X1515_CELLS_TO_WIN_MIN - 1).unwrap_err();
-        BoardParams::init(BoardSize::X1515, Level::Easy, X1515_CELLS_TO_WIN_MAX + 1).unwrap_err();
-        BoardParams::init(BoardSize::X1515, Level::Normal, X1515_CELLS_TO_WIN_MIN - 1).unwrap_err();
-        BoardParams::init(BoardSize::X1515, Level::Normal, X1515_CELLS_TO_WIN_MAX + 1).unwrap_err();
+        for (boards, error) in test_cases {
+            assert_eq!(BoardTable { boards }.validate(), Err(error));
+        }
+        assert!(matches!(
+            BoardTable::from_json(r#"[{"rows": 3, "cols": 3}]"#),
+            Err(BoardTableError::Parse(_))
+        ));
     }
 }
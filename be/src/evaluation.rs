@@ -0,0 +1,249 @@
+use crate::models::WinRule;
+
+/// Heuristic board value kept up to date one move at a time.
+///
+/// Board value is the sum of the values of all windows of `cells_to_win`
+/// consecutive cells. Value of a window depends only on how many markers
+/// of each player it contains, so placing or removing a marker requires
+/// updating only the windows containing that cell.
+///
+/// Depending on `win_rule`, markers in the flanking cells of a window may
+/// prevent the player from winning with it, in which case the window is
+/// dead for the player and worthless to it.
+#[derive(Clone, Debug)]
+pub struct WindowEvaluation {
+    windows: Vec<Vec<usize>>,
+    cell_windows: Vec<Vec<usize>>,
+    // Windows flanked by the cell
+    cell_flanked_windows: Vec<Vec<usize>>,
+    // Marker counts of the windows, indexed like player bitboards
+    window_marks: Vec<[u8; 2]>,
+    // Marker counts of the flanking cells of the windows
+    flank_marks: Vec<[u8; 2]>,
+    // Window value by the marker counts (p1 count * (cells_to_win + 1) + bot count)
+    window_values: Vec<i32>,
+    cells_to_win: usize,
+    win_rule: WinRule,
+    // Index of the black player of renju
+    black: usize,
+    total: i32,
+}
+
+impl WindowEvaluation {
+    /// Windows and their flanking cells `flanks` are given in the same order.
+    /// Marker `black_mark` is used only by the renju rule.
+    pub fn new(
+        windows: &[Vec<usize>],
+        flanks: &[Vec<usize>],
+        cells: &[i8],
+        empty_mark: i8,
+        window_values: Vec<i32>,
+        win_rule: WinRule,
+        black_mark: i8,
+    ) -> Self {
+        let cells_to_win = windows.first().map_or(0, |window| window.len());
+        let mut cell_windows = vec![Vec::new(); cells.len()];
+        let mut cell_flanked_windows = vec![Vec::new(); cells.len()];
+        let mut window_marks = vec![[0; 2]; windows.len()];
+        let mut flank_marks = vec![[0; 2]; windows.len()];
+
+        for (w, (window, window_flanks)) in windows.iter().zip(flanks.iter()).enumerate() {
+            for &idx in window.iter() {
+                cell_windows[idx].push(w);
+
+                if cells[idx] != empty_mark {
+                    window_marks[w][(cells[idx] > 0) as usize] += 1;
+                }
+            }
+            for &idx in window_flanks.iter() {
+                cell_flanked_windows[idx].push(w);
+
+                if cells[idx] != empty_mark {
+                    flank_marks[w][(cells[idx] > 0) as usize] += 1;
+                }
+            }
+        }
+
+        let mut evaluation = WindowEvaluation {
+            windows: windows.to_vec(),
+            cell_windows,
+            cell_flanked_windows,
+            window_marks,
+            flank_marks,
+            window_values,
+            cells_to_win,
+            win_rule,
+            black: (black_mark > 0) as usize,
+            total: 0,
+        };
+        evaluation.total = (0..windows.len()).map(|w| evaluation.window_value(w)).sum();
+
+        evaluation
+    }
+
+    pub fn total(&self) -> i32 {
+        self.total
+    }
+
+    /// Windows having `count` markers of `player` and none of the opponent,
+    /// excluding the windows dead for `player`.
+    pub fn open_windows(&self, player: i8, count: usize) -> impl Iterator<Item = &[usize]> {
+        let p = (player > 0) as usize;
+
+        self.windows
+            .iter()
+            .enumerate()
+            .filter(move |&(w, _)| {
+                let marks = self.window_marks[w];
+                marks[p] as usize == count && marks[1 - p] == 0 && !self.dead_for(w, p)
+            })
+            .map(|(_, window)| window.as_slice())
+    }
+
+    pub fn add(&mut self, index: usize, player: i8) {
+        self.update(index, player, |count| *count += 1);
+    }
+
+    pub fn remove(&mut self, index: usize, player: i8) {
+        self.update(index, player, |count| *count -= 1);
+    }
+
+    fn update(&mut self, index: usize, player: i8, change: impl Fn(&mut u8)) {
+        let p = (player > 0) as usize;
+
+        for i in 0..self.cell_windows[index].len() {
+            let w = self.cell_windows[index][i];
+
+            self.total -= self.window_value(w);
+            change(&mut self.window_marks[w][p]);
+            self.total += self.window_value(w);
+        }
+        if self.win_rule == WinRule::Freestyle {
+            // Flanking cells don't matter and their counts are left stale
+            return;
+        }
+        for i in 0..self.cell_flanked_windows[index].len() {
+            let w = self.cell_flanked_windows[index][i];
+
+            self.total -= self.window_value(w);
+            change(&mut self.flank_marks[w][p]);
+            self.total += self.window_value(w);
+        }
+    }
+
+    fn window_value(&self, w: usize) -> i32 {
+        let [p1_count, bot_count] = self.window_marks[w];
+        // Only windows of a single player have a value
+        let owner = (bot_count > 0) as usize;
+
+        if self.dead_for(w, owner) {
+            return 0;
+        }
+        self.window_values[p1_count as usize * (self.cells_to_win + 1) + bot_count as usize]
+    }
+
+    /// Whether player `p` (index to the player bitboards) can't win with window `w`.
+    fn dead_for(&self, w: usize, p: usize) -> bool {
+        match self.win_rule {
+            WinRule::Freestyle => false,
+            // Completing the window would make an overline
+            WinRule::Exact => self.flank_marks[w][p] > 0,
+            // Opponent has blocked both ends of the window
+            WinRule::Caro => self.flank_marks[w][1 - p] == 2,
+            WinRule::Renju => p == self.black && self.flank_marks[w][p] > 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_values_sum_of_marks(cells_to_win: usize) -> Vec<i32> {
+        // Value of a window is its bot marker count minus p1 marker count
+        (0..=cells_to_win)
+            .flat_map(|p1_count| (0..=cells_to_win).map(move |bot| bot as i32 - p1_count as i32))
+            .collect()
+    }
+
+    #[test]
+    fn incremental_total_3x3_rows() {
+        let windows = vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8]];
+        let cells = [1, 0, -1, 0, 1, 0, 0, 0, 0];
+        let mut evaluation = WindowEvaluation::new(
+            &windows,
+            &[vec![], vec![], vec![]],
+            &cells,
+            0,
+            window_values_sum_of_marks(3),
+            WinRule::Freestyle,
+            1,
+        );
+
+        assert_eq!(evaluation.total(), 1);
+
+        evaluation.add(8, -1);
+        evaluation.add(5, 1);
+        assert_eq!(evaluation.total(), 1);
+
+        evaluation.remove(8, -1);
+        assert_eq!(evaluation.total(), 2);
+
+        let open_windows: Vec<&[usize]> = evaluation.open_windows(1, 2).collect();
+        assert_eq!(open_windows, vec![&[3, 4, 5][..]]);
+    }
+
+    #[test]
+    fn incremental_total_exact_rule_1x5_rows() {
+        // Windows of three cells in a row of five cells
+        let windows = vec![vec![0, 1, 2], vec![1, 2, 3], vec![2, 3, 4]];
+        let flanks = vec![vec![3], vec![0, 4], vec![1]];
+        let cells = [0, 0, 1, 1, 0];
+        let mut evaluation = WindowEvaluation::new(
+            &windows,
+            &flanks,
+            &cells,
+            0,
+            window_values_sum_of_marks(3),
+            WinRule::Exact,
+            1,
+        );
+
+        // Window 0-2 is dead as its flank 3 has a bot marker
+        assert_eq!(evaluation.total(), 4);
+        assert_eq!(evaluation.open_windows(1, 2).count(), 2);
+
+        evaluation.add(0, 1);
+        assert_eq!(evaluation.total(), 2);
+        assert_eq!(evaluation.open_windows(1, 2).count(), 1);
+
+        evaluation.remove(0, 1);
+        assert_eq!(evaluation.total(), 4);
+    }
+
+    #[test]
+    fn incremental_total_caro_rule_1x5_rows() {
+        let windows = vec![vec![0, 1, 2], vec![1, 2, 3], vec![2, 3, 4]];
+        let flanks = vec![vec![3], vec![0, 4], vec![1]];
+        let cells = [-1, 0, 1, 0, 0];
+        let mut evaluation = WindowEvaluation::new(
+            &windows,
+            &flanks,
+            &cells,
+            0,
+            window_values_sum_of_marks(3),
+            WinRule::Caro,
+            1,
+        );
+
+        assert_eq!(evaluation.total(), 2);
+
+        // Window 1-3 gets blocked at both ends and window 2-4 mixed
+        evaluation.add(4, -1);
+        assert_eq!(evaluation.total(), 0);
+        assert_eq!(evaluation.open_windows(1, 1).count(), 0);
+
+        evaluation.remove(4, -1);
+        assert_eq!(evaluation.open_windows(1, 1).count(), 2);
+    }
+}
@@ -3,11 +3,11 @@ use rand::seq::SliceRandom;
 pub struct FirstMove;
 
 impl FirstMove {
-    pub fn find_bot_first_move_5x5(p1_mark_idx: usize) -> u8 {
+    pub fn find_bot_first_move_5x5(p1_mark_idx: usize) -> u16 {
         let center_idx = 12usize;
 
         match p1_mark_idx {
-            0 | 4 | 6 | 8 | 16 | 18 | 20 | 24 => center_idx as u8,
+            0 | 4 | 6 | 8 | 16 | 18 | 20 | 24 => center_idx as u16,
             5 | 9 => Self::get_random_or_fallback_idx(&[p1_mark_idx + 10], p1_mark_idx + 5),
             15 | 19 => Self::get_random_or_fallback_idx(&[p1_mark_idx - 10], p1_mark_idx - 5),
             1 | 21 => Self::get_random_or_fallback_idx(&[p1_mark_idx + 2], p1_mark_idx + 1),
@@ -54,11 +54,11 @@ impl FirstMove {
         }
     }
 
-    fn get_random_or_fallback_idx(indices: &[usize], fallback: usize) -> u8 {
+    fn get_random_or_fallback_idx(indices: &[usize], fallback: usize) -> u16 {
         if let Some(&rand_idx) = indices.choose(&mut rand::thread_rng()) {
-            rand_idx as u8
+            rand_idx as u16
         } else {
-            fallback as u8
+            fallback as u16
         }
     }
 }
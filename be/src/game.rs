@@ -1,19 +1,34 @@
-use std::collections::HashSet;
+use std::cmp;
 use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
 
 use rand::seq::SliceRandom;
 
-use crate::conf::{BoardParams, BoardSize, GameInitError};
-use crate::models::{Board, Level};
+use crate::bitboard::{Bitboard, LineWindows, LINE_DIRECTIONS};
+use crate::conf::{BoardParams, BoardTable, GameInitError, CAPTURES_TO_WIN, MCTS_ITERATIONS};
+use crate::evaluation::WindowEvaluation;
+use crate::models::{Board, Engine, Level, Topology, WinRule};
+use crate::transposition::Zobrist;
 
 const P1_MARK: i8 = -1;
 const BOT_MARK: i8 = 1;
 const EMPTY_MARK: i8 = 0;
+const BLOCKED_MARK: i8 = i8::MAX;
+// Cells beyond the board edge in the lines of renju checks
+const OFF_BOARD: i8 = i8::MIN;
 
 const WINNER_VALUE: i32 = 10000;
 const ONE_TO_WIN_VALUE: i32 = 500;
 const TWO_TO_WIN_VALUE: i32 = 100;
 const OPPONENT_PENALTY_MULTIPLIER: i32 = 3;
+const CAPTURED_PAIR_VALUE: i32 = 200;
+
+// Empty cells of boards with more cells are ordered by their value
+const UNORDERED_MAX_CELLS: usize = 16;
+
+// Boards with more cells consider only this many best valued candidate moves
+const UNPRUNED_MAX_CELLS: usize = 225;
+const MAX_CANDIDATE_MOVES: usize = 24;
 
 /// Type to represent the game parameters and board status.
 ///
@@ -32,6 +47,7 @@ const OPPONENT_PENALTY_MULTIPLIER: i32 = 3;
 ///     p1_mark: 1,
 ///     bot_mark: -1,
 ///     empty_mark: 0,
+///     ..Default::default()
 /// };
 ///
 /// let game = match Game::new(board, Level::Normal) {
@@ -43,6 +59,37 @@ const OPPONENT_PENALTY_MULTIPLIER: i32 = 3;
 /// assert_eq!(game.orig_bot_mark, -1);
 /// ```
 /// Next one would call struct Bot's `next_move` method with this `game` instance.
+///
+/// By default the bot searches exactly `max_depth` moves ahead. If `time_budget`
+/// is set, the search is deepened iteratively until the budget runs out and
+/// `max_depth` acts only as a ceiling.
+///
+/// With `engine` set to `Engine::Mcts` the bot uses Monte Carlo tree search
+/// instead, running `mcts_iterations` iterations or until `time_budget` runs out.
+///
+/// Minimax search splits the root moves between `search_threads` threads.
+///
+/// If `verbose` is true, the bot's move includes statistics of the search.
+///
+/// Lines of `cells_to_win` markers win according to `win_rule`. With the
+/// renju rule the player having made the first move is black and can't
+/// choose forbidden moves, see `is_forbidden`.
+///
+/// In games with `captures`, moves capture pairs of the opponent's markers,
+/// which are restored when the move is undone. Moves must therefore be undone
+/// in the reverse order of making them.
+///
+/// In `gravity` games the moves are restricted to the lowest empty cell of
+/// every column.
+///
+/// On a `Topology::Torus` board the lines wrap across the board edges.
+///
+/// In `misere` games `winner` still returns the player who made the line,
+/// use `game_winner` to get the actual winner of the game.
+///
+/// In `connect6` games every turn after the first one consists of two moves
+/// of the same player, use `next_player` to find the player of the next move.
+#[derive(Clone)]
 pub struct Game {
     pub cells: Vec<i8>,
     pub p1_mark: i8,
@@ -52,19 +99,123 @@ pub struct Game {
     pub orig_bot_mark: i8,
     pub orig_empty_mark: i8,
     pub level: Level,
-    pub board_size: BoardSize,
+    pub win_rule: WinRule,
+    pub captures: bool,
+    pub topology: Topology,
+    pub misere: bool,
+    pub connect6: bool,
+    pub gravity: bool,
     pub max_depth: usize,
+    pub time_budget: Option<Duration>,
+    pub engine: Engine,
+    pub mcts_iterations: u32,
+    pub search_threads: usize,
+    pub verbose: bool,
     cells_to_win: usize,
-    cells_offset: usize,
+    rows: usize,
+    cols: usize,
+    black_mark: i8,
+    zobrist: Zobrist,
+    hash: u64,
+    move_stack: Vec<usize>,
+    // Pairs captured by the players, indexed like the player bitboards
+    captured_pairs: [u8; 2],
+    // Cells captured by the moves in the move stack and the pair counts per move
+    captured_cells: Vec<usize>,
+    move_captures: Vec<u8>,
+    player_bits: [Bitboard; 2],
+    // Neutral cells neither player can use, windows through them are excluded
+    blocked: Bitboard,
+    line_windows: [LineWindows; 4],
+    evaluation: WindowEvaluation,
 }
 
 impl Game {
-    pub fn new(mut board: Board, level: Level) -> Result<Self, GameInitError> {
-        let board_params = BoardParams::new(&board, level)?;
+    #[allow(dead_code)]
+    pub fn new(board: Board, level: Level) -> Result<Self, GameInitError> {
+        Self::with_board_table(board, level, &BoardTable::default())
+    }
+
+    /// Like `new`, but the accepted board sizes and search depths are given by `board_table`.
+    pub fn with_board_table(
+        mut board: Board,
+        level: Level,
+        board_table: &BoardTable,
+    ) -> Result<Self, GameInitError> {
+        let board_params = BoardParams::new(&board, level, board_table)?;
+        let last_move = board.last_move.map(usize::from);
 
         Self::normalize_cell_values(&mut board);
 
-        Ok(Game {
+        let game = Self::from_params(board, level, board_params);
+
+        if let Some(index) = last_move {
+            if game.cells[index] == game.black_mark && game.is_forbidden(index) {
+                return Err(GameInitError::Forbidden);
+            }
+        }
+        Ok(game)
+    }
+
+    fn from_params(board: Board, level: Level, board_params: BoardParams) -> Self {
+        // Blocked cells are left out of the hash, bitboards and evaluation
+        let player_cells: Vec<i8> = board
+            .cells
+            .iter()
+            .map(|&cell| {
+                if cell == BLOCKED_MARK {
+                    EMPTY_MARK
+                } else {
+                    cell
+                }
+            })
+            .collect();
+        let blocked = Bitboard::from_indices(
+            (0..board.cells.len()).filter(|&idx| board.cells[idx] == BLOCKED_MARK),
+        );
+
+        let zobrist = Zobrist::new(board.cells.len());
+        let hash = zobrist.hash(&player_cells, EMPTY_MARK);
+        let player_bits = Self::player_bitboards(&player_cells, EMPTY_MARK);
+        let captured_pairs = [board.p1_captured_pairs, board.bot_captured_pairs];
+        // Black has made the first move, bot plays next
+        let placed_marks =
+            |p: usize| player_bits[p].count_ones() as usize + 2 * captured_pairs[1 - p] as usize;
+        let p1_black = if board.connect6 {
+            placed_marks(0) % 2 == 1
+        } else {
+            placed_marks(0) > placed_marks(1)
+        };
+        let black_mark = if p1_black { P1_MARK } else { BOT_MARK };
+
+        let mut line_windows = LineWindows::all(
+            board_params.rows,
+            board_params.cols,
+            board_params.to_win,
+            board.topology,
+        );
+        for windows in line_windows.iter_mut() {
+            windows.exclude(blocked, board_params.to_win);
+        }
+        let windows: Vec<Vec<usize>> = line_windows
+            .iter()
+            .flat_map(|windows| windows.window_cells(board_params.to_win))
+            .collect();
+        let flanks: Vec<Vec<usize>> = line_windows
+            .iter()
+            .flat_map(|windows| windows.window_flanks(board_params.to_win))
+            .collect();
+        let evaluation = WindowEvaluation::new(
+            &windows,
+            &flanks,
+            &player_cells,
+            EMPTY_MARK,
+            Self::window_value_table(board_params.to_win, board.misere),
+            board.win_rule,
+            black_mark,
+        );
+
+        let game = Game {
             cells: board.cells,
             p1_mark: P1_MARK,
             bot_mark: BOT_MARK,
@@ -73,45 +224,422 @@ impl Game {
             orig_bot_mark: board.bot_mark,
             orig_empty_mark: board.empty_mark,
             level,
-            board_size: board_params.size,
+            win_rule: board.win_rule,
+            captures: board.captures,
+            topology: board.topology,
+            misere: board.misere,
+            connect6: board.connect6,
+            gravity: board.gravity,
             max_depth: board_params.max_depth,
+            time_budget: None,
+            engine: Engine::default(),
+            mcts_iterations: MCTS_ITERATIONS,
+            search_threads: 1,
+            verbose: false,
             cells_to_win: board_params.to_win,
-            cells_offset: board_params.offset,
-        })
+            rows: board_params.rows,
+            cols: board_params.cols,
+            black_mark,
+            zobrist,
+            hash,
+            move_stack: Vec::new(),
+            captured_pairs,
+            captured_cells: Vec::new(),
+            move_captures: Vec::new(),
+            player_bits,
+            blocked,
+            line_windows,
+            evaluation,
+        };
+        debug_assert_eq!(game.evaluation.total(), game.value_in_total());
+
+        game
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Zobrist hash of the current board position.
+    pub fn hash(&self) -> u64 {
+        if self.captures {
+            return self.hash ^ self.zobrist.captures_key(self.captured_pairs);
+        }
+        self.hash
+    }
+
+    pub fn make_move(&mut self, index: usize, player: i8) {
+        self.place_marker(index, player);
+        self.move_stack.push(index);
+
+        if self.captures {
+            let pairs = self.capture_pairs(index, player);
+            self.captured_pairs[(player > 0) as usize] += pairs;
+            self.move_captures.push(pairs);
+        }
+    }
+
+    pub fn undo_move(&mut self, index: usize) {
+        let player = self.cells[index];
+
+        if self.captures {
+            let pairs = self.move_captures.pop().unwrap_or(0);
+            self.captured_pairs[(player > 0) as usize] -= pairs;
+
+            for _ in 0..2 * pairs {
+                if let Some(cell) = self.captured_cells.pop() {
+                    self.place_marker(cell, self.opponent(player));
+                }
+            }
+        }
+
+        self.remove_marker(index);
+        self.move_stack.pop();
+    }
+
+    /// Cells captured by the latest move made with `make_move`.
+    pub fn last_captures(&self) -> &[usize] {
+        let pairs = self.move_captures.last().copied().unwrap_or(0) as usize;
+        &self.captured_cells[self.captured_cells.len() - 2 * pairs..]
+    }
+
+    pub fn captured_pairs(&self, player: i8) -> u8 {
+        self.captured_pairs[(player > 0) as usize]
+    }
+
+    fn place_marker(&mut self, index: usize, player: i8) {
+        self.cells[index] = player;
+        self.hash ^= self.zobrist.key(index, player);
+        self.player_bits[(player > 0) as usize].set(index);
+        self.evaluation.add(index, player);
+    }
+
+    fn remove_marker(&mut self, index: usize) {
+        let player = self.cells[index];
+
+        self.cells[index] = self.empty_mark;
+        self.hash ^= self.zobrist.key(index, player);
+        self.player_bits[(player > 0) as usize].clear(index);
+        self.evaluation.remove(index, player);
+    }
+
+    /// Remove the pairs of opponent markers flanked by the marker of `player` at
+    /// `index` and another marker of `player`, returning the number of pairs.
+    fn capture_pairs(&mut self, index: usize, player: i8) -> u8 {
+        let opponent = self.opponent(player);
+        let mut pairs = 0;
+
+        for &(d_row, d_col) in LINE_DIRECTIONS.iter() {
+            for sign in [1, -1] {
+                let cells = [1, 2, 3]
+                    .map(|steps| self.cell_from(index, sign * d_row * steps, sign * d_col * steps));
+
+                if let [Some(first), Some(second), Some(end)] = cells {
+                    if self.cells[first] == opponent
+                        && self.cells[second] == opponent
+                        && self.cells[end] == player
+                    {
+                        self.remove_marker(first);
+                        self.remove_marker(second);
+                        self.captured_cells.extend([first, second]);
+                        pairs += 1;
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Index of the cell `d_row` rows and `d_col` columns away from `index`, if on the board.
+    fn cell_from(&self, index: usize, d_row: isize, d_col: isize) -> Option<usize> {
+        let (rows, cols) = (self.rows as isize, self.cols as isize);
+        let row = index as isize / cols + d_row;
+        let col = index as isize % cols + d_col;
+
+        if self.topology == Topology::Torus {
+            return Some((row.rem_euclid(rows) * cols + col.rem_euclid(cols)) as usize);
+        }
+        if row < 0 || row >= rows || col < 0 || col >= cols {
+            None
+        } else {
+            Some((row * cols + col) as usize)
+        }
+    }
+
+    /// Latest move made with `make_move` and not yet undone.
+    pub fn last_move(&self) -> Option<usize> {
+        self.move_stack.last().copied()
+    }
+
+    /// Player making the next move after `player` has made the latest one.
+    pub fn next_player(&self, player: i8) -> i8 {
+        if self.connect6 {
+            self.side_to_move()
+        } else {
+            self.opponent(player)
+        }
+    }
+
+    pub fn opponent(&self, player: i8) -> i8 {
+        if player == self.bot_mark {
+            self.p1_mark
+        } else {
+            self.bot_mark
+        }
     }
 
     pub fn empty_cell_count(&self) -> usize {
-        Self::empty_cells(&self.cells, self.empty_mark)
+        self.cells.len() - self.marker_count() - self.blocked.count_ones() as usize
+    }
+
+    /// Number of player markers on the board, blocked cells excluded.
+    pub fn marker_count(&self) -> usize {
+        let occupied = self.player_bits[0] | self.player_bits[1];
+        occupied.count_ones() as usize
+    }
+
+    pub fn has_blocked_cells(&self) -> bool {
+        !self.blocked.is_empty()
     }
 
     pub fn empty_cell_indices(&mut self) -> Vec<usize> {
-        let mut free_indices: Vec<usize> = self
-            .cells
+        let mut free_indices = self.playable_cell_indices();
+
+        free_indices.shuffle(&mut rand::thread_rng());
+
+        if self.cells.len() <= UNORDERED_MAX_CELLS {
+            return free_indices;
+        }
+        // Columns are few enough to consider every drop move
+        if !self.gravity {
+            let adjacent_indices: Vec<usize> = free_indices
+                .iter()
+                .copied()
+                .filter(|&index| self.adjacent_cell_occupied(index))
+                .collect();
+
+            // Markers walled in by blocked cells have no empty neighbours
+            if !adjacent_indices.is_empty() {
+                free_indices = adjacent_indices;
+            }
+        }
+
+        let mut candidates = self.reorder_empty_cell_indices_by_value(free_indices);
+
+        if self.cells.len() > UNPRUNED_MAX_CELLS {
+            candidates.truncate(MAX_CANDIDATE_MOVES);
+        }
+        candidates
+    }
+
+    /// Every cell the side to move may play, without pruning any of them.
+    pub fn playable_cell_indices(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = if self.gravity {
+            self.drop_cell_indices().collect()
+        } else {
+            self.cells
+                .iter()
+                .enumerate()
+                .filter(|(_, &cell)| cell == self.empty_mark)
+                .map(|(c_idx, _)| c_idx)
+                .collect()
+        };
+
+        if self.win_rule == WinRule::Renju && self.side_to_move() == self.black_mark {
+            indices.retain(|&index| !self.is_forbidden(index));
+        }
+        indices
+    }
+
+    /// Player making the next move, assuming that `black_mark` made the first one.
+    pub fn side_to_move(&self) -> i8 {
+        let occupied = self.marker_count();
+
+        if self.connect6 {
+            // Captured markers were placed too, and they change the turn in Connect6
+            let placed = occupied + 2 * self.captured_pairs.iter().sum::<u8>() as usize;
+
+            return if placed == 0 || (placed - 1) / 2 % 2 == 1 {
+                self.black_mark
+            } else {
+                self.opponent(self.black_mark)
+            };
+        }
+
+        if occupied.is_multiple_of(2) {
+            self.black_mark
+        } else {
+            self.opponent(self.black_mark)
+        }
+    }
+
+    /// Whether a move of black to `index` is forbidden by the renju rule.
+    ///
+    /// Move is forbidden if it makes an overline, two fours or two open threes,
+    /// unless it also makes a line of exactly `cells_to_win` markers. A three
+    /// is open if one more marker makes it a straight four, without checking
+    /// whether that marker itself would be forbidden.
+    pub fn is_forbidden(&self, index: usize) -> bool {
+        if self.win_rule != WinRule::Renju {
+            return false;
+        }
+        let (black, center) = (self.black_mark, self.cells_to_win);
+
+        let mut lines: Vec<Vec<i8>> = LINE_DIRECTIONS
             .iter()
-            .enumerate()
-            .filter(|(_, &cell)| cell == self.empty_mark)
-            .map(|(c_idx, _)| c_idx)
+            .map(|&(d_row, d_col)| {
+                let mut line = self.line_through(index, d_row, d_col, center);
+                line[center] = black;
+                line
+            })
+            .collect();
+        let runs: Vec<usize> = lines
+            .iter()
+            .map(|line| Self::run_through(line, center, black))
             .collect();
 
-        free_indices.shuffle(&mut rand::thread_rng());
+        if runs.contains(&self.cells_to_win) {
+            return false;
+        }
+        if runs.iter().any(|&run| run > self.cells_to_win) {
+            return true;
+        }
+
+        let (mut fours, mut threes) = (0, 0);
+
+        for line in lines.iter_mut() {
+            // Threes have at least two markers less than a full line
+            if line.iter().filter(|&&cell| cell == black).count() + 2 < self.cells_to_win {
+                continue;
+            }
+            let line_fours = Self::four_count(line, self.cells_to_win, black);
+            fours += line_fours;
 
-        match self.board_size {
-            BoardSize::X33 | BoardSize::X44 => free_indices,
-            _ => self.reorder_empty_cell_indices_by_value(free_indices),
+            if line_fours == 0 && Self::open_three(line, self.cells_to_win, black) {
+                threes += 1;
+            }
         }
+        fours >= 2 || threes >= 2
+    }
+
+    /// Cells within `reach` on both sides of `index` along a line, in the line order.
+    fn line_through(&self, index: usize, d_row: isize, d_col: isize, reach: usize) -> Vec<i8> {
+        let reach = reach as isize;
+
+        (-reach..=reach)
+            .map(|offset| {
+                self.cell_from(index, offset * d_row, offset * d_col)
+                    .map_or(OFF_BOARD, |idx| self.cells[idx])
+            })
+            .collect()
+    }
+
+    fn run_through(line: &[i8], center: usize, player: i8) -> usize {
+        let before = line[..center]
+            .iter()
+            .rev()
+            .take_while(|&&cell| cell == player)
+            .count();
+        let after = line[center + 1..]
+            .iter()
+            .take_while(|&&cell| cell == player)
+            .count();
+
+        before + 1 + after
+    }
+
+    /// Empty cells of the line making a run of exactly `cells_to_win` markers
+    /// through the center cell at index `cells_to_win`.
+    fn five_cells(line: &mut [i8], cells_to_win: usize, player: i8) -> Vec<usize> {
+        (0..line.len())
+            .filter(|&i| {
+                if line[i] != EMPTY_MARK {
+                    return false;
+                }
+                line[i] = player;
+                let five = Self::run_through(line, cells_to_win, player) == cells_to_win;
+                line[i] = EMPTY_MARK;
+                five
+            })
+            .collect()
+    }
+
+    fn four_count(line: &mut [i8], cells_to_win: usize, player: i8) -> usize {
+        match Self::five_cells(line, cells_to_win, player)[..] {
+            // Straight four, two ways to complete the same run
+            [first, second] if second - first == cells_to_win => 1,
+            ref cells => cells.len(),
+        }
+    }
+
+    fn open_three(line: &mut [i8], cells_to_win: usize, player: i8) -> bool {
+        (0..line.len()).any(|i| {
+            if line[i] != EMPTY_MARK {
+                return false;
+            }
+            line[i] = player;
+            let straight_four = matches!(
+                Self::five_cells(line, cells_to_win, player)[..],
+                [first, second] if second - first == cells_to_win
+            );
+            line[i] = EMPTY_MARK;
+            straight_four
+        })
+    }
+
+    /// Whether a marker can be placed to the cell, in gravity games only on the
+    /// bottom row or on top of another marker.
+    pub fn is_playable(&self, index: usize) -> bool {
+        if self.cells[index] != self.empty_mark {
+            return false;
+        }
+        let below = index + self.cols;
+        !self.gravity || below >= self.cells.len() || self.cells[below] != self.empty_mark
+    }
+
+    /// Lowest empty cell of every column which isn't full.
+    fn drop_cell_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.cols).filter_map(move |col| {
+            (0..self.rows)
+                .rev()
+                .map(|row| row * self.cols + col)
+                .find(|&idx| self.cells[idx] == self.empty_mark)
+        })
     }
 
     pub fn heuristic_game_value(&self, winner: i8, depth: i32) -> i32 {
         let depth_unzero = depth + 1;
 
         if winner != self.empty_mark {
-            return winner as i32 * WINNER_VALUE * depth_unzero;
+            return self.game_winner(winner) as i32 * WINNER_VALUE * depth_unzero;
         }
 
-        self.value_in_total() * depth_unzero
+        self.position_value() * depth_unzero
+    }
+
+    /// Heuristic value of the lines and the captured pairs.
+    fn position_value(&self) -> i32 {
+        let [p1_pairs, bot_pairs] = self.captured_pairs;
+        self.evaluation.total() + (bot_pairs as i32 - p1_pairs as i32) * CAPTURED_PAIR_VALUE
+    }
+
+    /// Winner of the game when `winner` has made `cells_to_win` in a row.
+    pub fn game_winner(&self, winner: i8) -> i8 {
+        if self.misere && winner != self.empty_mark {
+            self.opponent(winner)
+        } else {
+            winner
+        }
     }
 
     pub fn winner(&self) -> i8 {
+        if let Some(winner) = self.winner_by_captures() {
+            return winner;
+        }
         let winner_in_row = self.winner_in_row();
         if winner_in_row != self.empty_mark {
             return winner_in_row;
@@ -131,88 +659,132 @@ impl Game {
         self.empty_mark
     }
 
-    fn adjacent_cell_occupied(&self, index: usize) -> bool {
-        let offset = self.cells_offset;
-        let cells_count = self.cells.len();
-
-        let adjacent_indices = if index == 0 {
-            // First row three cases
-            vec![index + 1, index + offset, index + offset + 1]
-        } else if index == offset - 1 {
-            vec![index - 1, index + (offset - 1), index + offset]
-        } else if index < offset {
-            vec![
-                index - 1,
-                index + 1,
-                index + (offset - 1),
-                index + offset,
-                index + offset + 1,
-            ]
-        } else if index == cells_count - offset {
-            // Last row three cases
-            vec![index - offset, index - (offset - 1), index + 1]
-        } else if index == cells_count - 1 {
-            vec![index - (offset + 1), index - offset, index - 1]
-        } else if index > cells_count - offset {
-            vec![
-                index - (offset + 1),
-                index - offset,
-                index - (offset - 1),
-                index - 1,
-                index + 1,
-            ]
-        } else if index % offset == 0 {
-            // Leftmost column middle
-            vec![
-                index - offset,
-                index - (offset - 1),
-                index + 1,
-                index + offset,
-                index + offset + 1,
-            ]
-        } else if (index + 1) % offset == 0 {
-            // Rightmost column middle
-            vec![
-                index - (offset + 1),
-                index - offset,
-                index - 1,
-                index + (offset - 1),
-                index + offset,
-            ]
-        } else {
-            // Other
-            vec![
-                index - (offset + 1),
-                index - offset,
-                index - (offset - 1),
-                index - 1,
-                index + 1,
-                index + (offset - 1),
-                index + offset,
-                index + offset + 1,
-            ]
-        };
+    /// Winner of the game considering only the lines through cell `index`.
+    ///
+    /// This is enough to detect a win made by the latest move to `index`
+    /// provided that the game had no winner before that move.
+    pub fn winner_after_move(&self, index: usize) -> i8 {
+        let player = self.cells[index];
 
-        adjacent_indices
-            .iter()
-            .any(|&idx| self.cells[idx] != self.empty_mark)
+        if player == self.empty_mark {
+            return self.empty_mark;
+        }
+        if self.captured_pairs(player) >= CAPTURES_TO_WIN {
+            return player;
+        }
+
+        for &(d_row, d_col) in LINE_DIRECTIONS.iter() {
+            let (forward_length, forward_end) = self.run_length_from(index, d_row, d_col, player);
+            let (backward_length, backward_end) =
+                self.run_length_from(index, -d_row, -d_col, player);
+            // Run wrapping around the torus back to `index` covers the whole line
+            let run_length = if forward_end == Some(player) {
+                1 + forward_length
+            } else {
+                1 + forward_length + backward_length
+            };
+
+            let opponent = Some(self.opponent(player));
+            let wins = match self.win_rule {
+                WinRule::Freestyle => run_length >= self.cells_to_win,
+                WinRule::Exact => run_length == self.cells_to_win,
+                WinRule::Renju if player == self.black_mark => run_length == self.cells_to_win,
+                WinRule::Renju => run_length >= self.cells_to_win,
+                // Windows of an overline have the player's own marker at one end
+                WinRule::Caro => {
+                    run_length > self.cells_to_win
+                        || run_length == self.cells_to_win
+                            && (forward_end != opponent || backward_end != opponent)
+                }
+            };
+            if wins {
+                return player;
+            }
+        }
+        self.empty_mark
     }
 
-    fn empty_cells(slice: &[i8], empty_mark: i8) -> usize {
-        slice.iter().filter(|&cell| *cell == empty_mark).count()
+    /// Playable empty cells of the windows `player` can still complete and which
+    /// lack exactly `missing` markers of the player, sorted and deduplicated.
+    ///
+    /// E.g. with `missing` 1 these are the cells winning the game immediately.
+    pub fn threat_cells(&self, player: i8, missing: usize) -> Vec<usize> {
+        if missing > self.cells_to_win {
+            return Vec::new();
+        }
+        let mut cells: Vec<usize> = self
+            .evaluation
+            .open_windows(player, self.cells_to_win - missing)
+            .flat_map(|window| window.iter().copied())
+            .filter(|&idx| self.is_playable(idx))
+            .filter(|&idx| player != self.black_mark || !self.is_forbidden(idx))
+            .collect();
+
+        cells.sort_unstable();
+        cells.dedup();
+        cells
     }
 
-    fn reorder_empty_cell_indices_by_value(&mut self, free_indices: Vec<usize>) -> Vec<usize> {
-        let (free_indices_w_adj, _): (Vec<usize>, Vec<usize>) = free_indices
+    /// Length of the run of `player` markers starting next to `index` and the
+    /// marker right after the run, None if the run ends to the board edge.
+    ///
+    /// On a torus the run may wrap back to `index`, the marker after the run
+    /// being then the marker of `player` at `index`.
+    fn run_length_from(
+        &self,
+        index: usize,
+        d_row: isize,
+        d_col: isize,
+        player: i8,
+    ) -> (usize, Option<i8>) {
+        let mut cell_idx = index;
+        let mut length = 0;
+
+        loop {
+            cell_idx = match self.cell_from(cell_idx, d_row, d_col) {
+                Some(next_idx) => next_idx,
+                None => return (length, None),
+            };
+            let cell = self.cells[cell_idx];
+            if cell != player || cell_idx == index {
+                return (length, Some(cell));
+            }
+            length += 1;
+        }
+    }
+
+    fn adjacent_cell_occupied(&self, index: usize) -> bool {
+        // Neighbours in both directions of every line, wrapping around on a torus
+        LINE_DIRECTIONS
             .iter()
-            .partition(|&index| self.adjacent_cell_occupied(*index));
+            .flat_map(|&(d_row, d_col)| [(d_row, d_col), (-d_row, -d_col)])
+            .filter_map(|(d_row, d_col)| self.cell_from(index, d_row, d_col))
+            .any(|idx| self.cells[idx] == self.p1_mark || self.cells[idx] == self.bot_mark)
+    }
 
-        let mut value_map: Vec<(u32, i32)> = Vec::with_capacity(free_indices_w_adj.len());
+    fn player_bitboards(cells: &[i8], empty_mark: i8) -> [Bitboard; 2] {
+        // Like Zobrist keys, indexed by the sign of the normalized marker
+        let mut player_bits = [Bitboard::default(); 2];
 
-        for &idx in free_indices_w_adj.iter() {
-            self.cells[idx] = self.bot_mark;
-            value_map.push((idx as u32, self.value_in_total()));
-            self.cells[idx] = self.empty_mark;
+        for (idx, &cell) in cells.iter().enumerate() {
+            if cell != empty_mark {
+                player_bits[(cell > 0) as usize].set(idx);
+            }
+        }
+        player_bits
+    }
+
+    fn mark_bits(&self, mark: i8) -> Bitboard {
+        self.player_bits[(mark > 0) as usize]
+    }
+
+    fn reorder_empty_cell_indices_by_value(&mut self, free_indices: Vec<usize>) -> Vec<usize> {
+        let mut value_map: Vec<(u32, i32)> = Vec::with_capacity(free_indices.len());
+
+        for &idx in free_indices.iter() {
+            self.make_move(idx, self.bot_mark);
+            value_map.push((idx as u32, self.position_value()));
+            self.undo_move(idx);
         }
         value_map.sort_unstable_by_key(|&pair| -pair.1);
 
@@ -225,128 +797,65 @@ impl Game {
                 *cell = P1_MARK;
             } else if *cell == board.bot_mark {
                 *cell = BOT_MARK;
+            } else if Some(*cell) == board.blocked_mark {
+                *cell = BLOCKED_MARK;
             } else {
                 *cell = EMPTY_MARK;
             }
         }
     }
 
-    fn winner_in_row(&self) -> i8 {
-        let max_start_col = self.cells_offset - self.cells_to_win + 1;
-
-        for start_col in 0..max_start_col {
-            for (i, &row_first) in self
-                .cells
-                .iter()
-                .enumerate()
-                .skip(start_col)
-                .step_by(self.cells_offset)
-            {
-                if row_first != self.empty_mark
-                    && self
-                        .cells
-                        .iter()
-                        .skip(i)
-                        .take(self.cells_to_win)
-                        .all(|&cell| cell == row_first)
-                {
-                    return row_first;
-                }
-            }
-        }
-        self.empty_mark
+    fn winner_by_captures(&self) -> Option<i8> {
+        [self.p1_mark, self.bot_mark]
+            .into_iter()
+            .find(|&mark| self.captured_pairs(mark) >= CAPTURES_TO_WIN)
     }
 
-    fn winner_in_col(&self) -> i8 {
-        let max_start_row = self.cells_offset - self.cells_to_win + 1;
-
-        for start_row in 0..max_start_row {
-            for (i, &col_first) in self
-                .cells
-                .iter()
-                .enumerate()
-                .skip(start_row * self.cells_offset)
-                .take(self.cells_offset)
-            {
-                if col_first != self.empty_mark
-                    && self
-                        .cells
-                        .iter()
-                        .skip(i)
-                        .step_by(self.cells_offset)
-                        .take(self.cells_to_win)
-                        .all(|&cell| cell == col_first)
-                {
-                    return col_first;
-                }
-            }
-        }
-        self.empty_mark
+    fn winner_in_row(&self) -> i8 {
+        self.winner_in_line(&self.line_windows[0])
     }
 
-    fn diag_start_search_indices(&self, antidiag: bool) -> HashSet<usize> {
-        let offset = self.cells_offset;
-        let max_row = offset - self.cells_to_win + 1;
-        let max_col = max_row;
-
-        let start_search_indices: HashSet<usize> = if antidiag {
-            let anti_offset = self.cells_to_win - 1;
-            (0..max_row)
-                .flat_map(|row| (row * offset + anti_offset..row * offset + anti_offset + max_col))
-                .collect()
-        } else {
-            (0..max_row)
-                .flat_map(|row| (row * offset..row * offset + max_col))
-                .collect()
-        };
-
-        start_search_indices
+    fn winner_in_col(&self) -> i8 {
+        self.winner_in_line(&self.line_windows[1])
     }
 
     fn winner_in_diag(&self) -> i8 {
-        let offset = self.cells_offset;
-        let max_row = offset - self.cells_to_win + 1;
-
-        let start_search_indices = self.diag_start_search_indices(false);
-
-        self.winner_general_diag(offset + 1, max_row, &start_search_indices)
+        self.winner_in_line(&self.line_windows[2])
     }
 
     fn winner_in_antidiag(&self) -> i8 {
-        let offset = self.cells_offset;
-        let max_row = offset - self.cells_to_win + 1;
+        self.winner_in_line(&self.line_windows[3])
+    }
 
-        let start_search_indices = self.diag_start_search_indices(true);
+    fn winner_in_line(&self, windows: &LineWindows) -> i8 {
+        for mark in [self.p1_mark, self.bot_mark] {
+            let full_windows = windows.full_windows(self.mark_bits(mark), self.cells_to_win);
 
-        self.winner_general_diag(offset - 1, max_row, &start_search_indices)
+            if !(full_windows & !self.dead_windows(windows, mark)).is_empty() {
+                return mark;
+            }
+        }
+        self.empty_mark
     }
 
-    fn winner_general_diag(
-        &self,
-        diag_offset: usize,
-        max_row: usize,
-        start_search_indices: &HashSet<usize>,
-    ) -> i8 {
-        for (i, &diag_first) in self
-            .cells
-            .iter()
-            .enumerate()
-            .take(max_row * self.cells_offset)
-            .filter(|(i, _)| start_search_indices.contains(i))
-        {
-            if diag_first != self.empty_mark
-                && self
-                    .cells
-                    .iter()
-                    .skip(i)
-                    .step_by(diag_offset)
-                    .take(self.cells_to_win)
-                    .all(|&cell| cell == diag_first)
-            {
-                return diag_first;
+    /// Start cells of the windows `player` can't win with due to `win_rule`.
+    fn dead_windows(&self, windows: &LineWindows, player: i8) -> Bitboard {
+        match self.win_rule {
+            WinRule::Freestyle => Bitboard::default(),
+            WinRule::Renju if player != self.black_mark => Bitboard::default(),
+            WinRule::Exact | WinRule::Renju => {
+                // Own marker in a flanking cell makes an overline
+                let [before, after] =
+                    windows.flanked_windows(self.mark_bits(player), self.cells_to_win);
+                before | after
+            }
+            WinRule::Caro => {
+                let opponent = self.opponent(player);
+                let [before, after] =
+                    windows.flanked_windows(self.mark_bits(opponent), self.cells_to_win);
+                before & after
             }
         }
-        self.empty_mark
     }
 
     fn value_in_total(&self) -> i32 {
@@ -362,76 +871,71 @@ impl Game {
     }
 
     fn value_in_rows(&self) -> i32 {
-        let max_start_col = self.cells_offset - self.cells_to_win + 1;
-        let mut value = 0;
-
-        for start_col in 0..max_start_col {
-            for row in 0..self.cells_offset {
-                let start_cell = row * self.cells_offset + start_col;
-                let row_slice = &self.cells[start_cell..start_cell + self.cells_to_win];
-
-                let row_sum = row_slice.iter().sum::<i8>();
-                let empty_cells = Self::empty_cells(row_slice, self.empty_mark);
-                value +=
-                    Self::compute_value_from_window_sum(row_sum, empty_cells, self.cells_to_win);
-            }
-        }
-        value
+        self.value_in_line(&self.line_windows[0])
     }
 
     fn value_in_cols(&self) -> i32 {
-        let max_start_row = self.cells_offset - self.cells_to_win + 1;
-        let mut value = 0;
-
-        for start_row in 0..max_start_row {
-            for col in 0..self.cells_offset {
-                let start_cell = start_row * self.cells_offset + col;
-                let col_slice: Vec<i8> = self.cells[start_cell..]
-                    .iter()
-                    .step_by(self.cells_offset)
-                    .take(self.cells_to_win)
-                    .copied()
-                    .collect();
-
-                let col_sum = col_slice.iter().sum::<i8>();
-                let empty_cells = Self::empty_cells(&col_slice, self.empty_mark);
-                value +=
-                    Self::compute_value_from_window_sum(col_sum, empty_cells, self.cells_to_win);
-            }
-        }
-        value
+        self.value_in_line(&self.line_windows[1])
     }
 
     fn value_in_diags(&self, antidiag: bool) -> i32 {
-        let diag_offset = if antidiag {
-            self.cells_offset - 1
-        } else {
-            self.cells_offset + 1
-        };
+        self.value_in_line(&self.line_windows[if antidiag { 3 } else { 2 }])
+    }
 
-        let start_search_indices = self.diag_start_search_indices(antidiag);
+    fn value_in_line(&self, windows: &LineWindows) -> i32 {
+        let counts = windows.window_counts(self.player_bits, self.cells_to_win);
         let mut value = 0;
 
-        for &start_cell in start_search_indices.iter() {
-            let diag_slice: Vec<i8> = self.cells[start_cell..]
-                .iter()
-                .step_by(diag_offset)
-                .take(self.cells_to_win)
-                .copied()
-                .collect();
-
-            let diag_sum = diag_slice.iter().sum::<i8>();
-            let empty_cells = Self::empty_cells(&diag_slice, self.empty_mark);
-            value += Self::compute_value_from_window_sum(diag_sum, empty_cells, self.cells_to_win);
+        // Only windows without the opponent's markers can have nonzero value
+        // and for them counts less than half of the window yield zero
+        let min_count = cmp::max(1, self.cells_to_win >> 1);
+
+        for (p, sign) in [(0, -1), (1, 1)] {
+            let dead_windows = self.dead_windows(windows, sign);
+
+            for count in min_count..=self.cells_to_win {
+                let windows_count =
+                    (counts.open_windows(p, count) & !dead_windows).count_ones() as i32;
+
+                if windows_count > 0 {
+                    value += windows_count
+                        * Self::compute_value_from_window_sum(
+                            sign * count as i8,
+                            self.cells_to_win - count,
+                            self.cells_to_win,
+                            self.misere,
+                        );
+                }
+            }
         }
         value
     }
 
+    fn window_value_table(cells_to_win: usize, misere: bool) -> Vec<i32> {
+        // Value of a window by its p1 and bot marker counts, mixed windows are worthless
+        (0..=cells_to_win)
+            .flat_map(|p1_count| {
+                (0..=cells_to_win).map(move |bot_count| match (p1_count, bot_count) {
+                    (0, count) | (count, 0) if count > 0 => Self::compute_value_from_window_sum(
+                        bot_count as i8 - p1_count as i8,
+                        cells_to_win - count,
+                        cells_to_win,
+                        misere,
+                    ),
+                    _ => 0,
+                })
+            })
+            .collect()
+    }
+
     fn compute_value_from_window_sum(
         window_sum: i8,
         window_empty_cells: usize,
         window: usize,
+        misere: bool,
     ) -> i32 {
+        // In misère lines of the bot are as bad for it as lines of p1 otherwise
+        let window_sum = if misere { -window_sum } else { window_sum };
         let window_sum = window_sum as i32;
         let window_sum_abs = window_sum.unsigned_abs() as usize;
         let window_thres = window >> 1;
@@ -458,7 +962,7 @@ impl Game {
 
 impl Display for Game {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let show_cells_in_row = self.board_size as usize;
+        let show_cells_in_row = self.cols;
 
         for row in self.cells.chunks(show_cells_in_row) {
             let row_repr = row
@@ -468,6 +972,8 @@ impl Display for Game {
                         'x'
                     } else if val == self.p1_mark {
                         'o'
+                    } else if val == BLOCKED_MARK {
+                        '#'
                     } else {
                         '-'
                     }
@@ -497,6 +1003,7 @@ mod tests {
             p1_mark,
             bot_mark,
             empty_mark,
+            ..Default::default()
         };
         match Game::new(board, Level::Normal) {
             Ok(game) => game,
@@ -512,30 +1019,84 @@ mod tests {
         cells_to_win: u8,
     ) -> Game {
         // Use this e.g. to init an inconsistent game board
-        Game {
+        let board = Board {
             cells: cells.to_vec(),
+            cells_to_win,
             p1_mark,
             bot_mark,
             empty_mark,
-            orig_p1_mark: p1_mark,
-            orig_bot_mark: bot_mark,
-            orig_empty_mark: empty_mark,
-            level: Level::Normal,
-            board_size: BoardSize::X55,
+            ..Default::default()
+        };
+        let board_params = BoardParams {
+            rows: 5,
+            cols: 5,
+            to_win: cells_to_win.into(),
             max_depth: 7,
-            cells_offset: BoardSize::X55 as usize,
-            cells_to_win: cells_to_win.into(),
+        };
+        Game::from_params(board, Level::Normal, board_params)
+    }
+
+    fn init_rect_game(cells: &[i8], rows: u8, cols: u8, cells_to_win: u8) -> Game {
+        init_rule_game(cells, rows, cols, cells_to_win, WinRule::Freestyle)
+    }
+
+    fn init_rule_game(
+        cells: &[i8],
+        rows: u8,
+        cols: u8,
+        cells_to_win: u8,
+        win_rule: WinRule,
+    ) -> Game {
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(rows),
+            cols: Some(cols),
+            win_rule,
+            ..Default::default()
+        };
+        match Game::new(board, Level::Normal) {
+            Ok(game) => game,
+            Err(error_kind) => panic!("Game::new(): {:?}", error_kind),
         }
     }
 
+    #[test]
+    fn game_with_custom_board_table() {
+        let json = r#"[{"rows": 4, "cols": 4, "cells_to_win_min": 3, "cells_to_win_max": 4, "easy_max_depth": 1, "normal_max_depth": 3}]"#;
+        let board_table = BoardTable::from_json(json).unwrap();
+
+        let board = |cells_count: usize, cells_to_win: u8| Board {
+            cells: vec![0; cells_count],
+            cells_to_win,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            ..Default::default()
+        };
+
+        let game = Game::with_board_table(board(16, 3), Level::Normal, &board_table).unwrap();
+        assert_eq!(game.max_depth, 3);
+        assert_eq!((game.rows(), game.cols()), (4, 4));
+
+        assert!(matches!(
+            Game::with_board_table(board(9, 3), Level::Normal, &board_table),
+            Err(GameInitError::Size)
+        ));
+    }
+
     #[test]
     fn board_size_error() {
         let board = Board {
             cells: vec![0; 10],
-            cells_to_win: X33_CELLS_TO_WIN_MIN,
+            cells_to_win: 3,
             p1_mark: -1,
             bot_mark: 1,
             empty_mark: 0,
+            ..Default::default()
         };
         match Game::new(board, Level::Normal) {
             Ok(_) => panic!("Game::new() returned Ok for size error."),
@@ -547,14 +1108,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn board_dimensions_error() {
+        for (rows, cols) in [(Some(6), None), (Some(6), Some(6)), (Some(7), Some(7))] {
+            let board = Board {
+                cells: vec![0; 42],
+                cells_to_win: 4,
+                p1_mark: -1,
+                bot_mark: 1,
+                empty_mark: 0,
+                rows,
+                cols,
+                ..Default::default()
+            };
+            assert!(
+                matches!(Game::new(board, Level::Normal), Err(GameInitError::Size)),
+                "rows {:?} cols {:?}",
+                rows,
+                cols
+            );
+        }
+    }
+
     #[test]
     fn board_mark_error() {
         let board = Board {
             cells: vec![0, 0, 0, 0, 0, 0, 0, 0, 2],
-            cells_to_win: X33_CELLS_TO_WIN_MIN,
+            cells_to_win: 3,
             p1_mark: 1,
             bot_mark: -1,
             empty_mark: 0,
+            ..Default::default()
         };
         match Game::new(board, Level::Normal) {
             Ok(_) => panic!("Game::new() returned Ok for marker error."),
@@ -566,6 +1150,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn blocked_mark_error() {
+        let board = |blocked_mark: i8| Board {
+            cells: vec![0, 2, 0, 0, 1, 0, 0, 2, 0],
+            cells_to_win: 3,
+            p1_mark: 1,
+            bot_mark: -1,
+            empty_mark: 0,
+            blocked_mark: Some(blocked_mark),
+            ..Default::default()
+        };
+
+        // Blocked cells are not counted for either player
+        let game = Game::new(board(2), Level::Normal).unwrap();
+        assert_eq!(game.empty_cell_count(), 6);
+        assert_eq!(game.marker_count(), 1);
+
+        for blocked_mark in [0, 1, -1] {
+            assert!(matches!(
+                Game::new(board(blocked_mark), Level::Normal),
+                Err(GameInitError::Marks)
+            ));
+        }
+    }
+
     #[test]
     fn board_inconsistent_error() {
         let cells_collections: [[i8; 9]; 2] =
@@ -574,10 +1183,11 @@ mod tests {
         for cells in &cells_collections {
             let board = Board {
                 cells: cells.to_vec(),
-                cells_to_win: X33_CELLS_TO_WIN_MAX,
+                cells_to_win: 3,
                 p1_mark: 1,
                 bot_mark: -1,
                 empty_mark: 0,
+                ..Default::default()
             };
             match Game::new(board, Level::Normal) {
                 Ok(_) => panic!("Game::new() returned Ok for inconsistent board."),
@@ -590,6 +1200,175 @@ mod tests {
         }
     }
 
+    #[test]
+    fn gravity_floating_marker_error() {
+        #[rustfmt::skip]
+        let cells: [i8; 42] = [
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0,
+            0, 0, 1, 0, 0, 0, 0,
+            0, 0, 0, -1, 0, 0, 0,
+        ];
+        let board = |gravity: bool| Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(6),
+            cols: Some(7),
+            gravity,
+            ..Default::default()
+        };
+
+        assert!(Game::new(board(false), Level::Normal).is_ok());
+        assert!(matches!(
+            Game::new(board(true), Level::Normal),
+            Err(GameInitError::Inconsistent)
+        ));
+    }
+
+    fn init_capture_game(cells: &[i8], captured_pairs: [u8; 2]) -> Result<Game, GameInitError> {
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 5,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(9),
+            cols: Some(9),
+            captures: true,
+            p1_captured_pairs: captured_pairs[0],
+            bot_captured_pairs: captured_pairs[1],
+            ..Default::default()
+        };
+        Game::new(board, Level::Normal)
+    }
+
+    #[test]
+    fn capture_make_and_undo_move_9x9() {
+        let mut cells = [0; 81];
+        cells[0] = 1;
+        cells[1] = -1;
+        cells[2] = -1;
+        cells[40] = -1;
+        cells[50] = 1;
+        let mut game = init_capture_game(&cells, [0, 0]).unwrap();
+        let hash = game.hash();
+
+        game.make_move(3, game.bot_mark);
+
+        assert_eq!(game.last_captures(), &[2, 1]);
+        assert_eq!(game.captured_pairs(game.bot_mark), 1);
+        assert_eq!((game.cells[1], game.cells[2]), (0, 0));
+        assert_eq!(game.evaluation.total(), game.value_in_total());
+        assert_ne!(game.hash(), hash);
+
+        game.undo_move(3);
+
+        assert_eq!(game.cells.to_vec(), cells.to_vec());
+        assert_eq!(game.captured_pairs(game.bot_mark), 0);
+        assert_eq!(game.evaluation.total(), game.value_in_total());
+        assert_eq!(game.hash(), hash);
+    }
+
+    #[test]
+    fn capture_fifth_pair_wins_9x9() {
+        let mut cells = [0; 81];
+        cells[0] = 1;
+        cells[1] = -1;
+        cells[2] = -1;
+        cells[40] = -1;
+        cells[50] = 1;
+        let mut game = init_capture_game(&cells, [4, 4]).unwrap();
+
+        assert_eq!(game.winner(), game.empty_mark);
+
+        game.make_move(3, game.bot_mark);
+
+        assert_eq!(game.winner(), game.bot_mark);
+        assert_eq!(game.winner_after_move(3), game.bot_mark);
+    }
+
+    fn init_connect6_game(cells: &[i8]) -> Result<Game, GameInitError> {
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 6,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(19),
+            cols: Some(19),
+            connect6: true,
+            ..Default::default()
+        };
+        Game::new(board, Level::Normal)
+    }
+
+    #[test]
+    fn connect6_next_player_19x19() {
+        let mut game = init_connect6_game(&[0; 361]).unwrap();
+        let (bot_mark, p1_mark) = (game.bot_mark, game.p1_mark);
+
+        // Bot starts with one move and then both players make two moves per turn
+        let players = [bot_mark, p1_mark, p1_mark, bot_mark, bot_mark, p1_mark];
+
+        for (idx, &player) in players.iter().enumerate() {
+            assert_eq!(game.side_to_move(), player, "move {}", idx + 1);
+            game.make_move(idx, player);
+        }
+        assert_eq!(game.next_player(p1_mark), p1_mark);
+
+        let mut cells = [0; 361];
+        cells[180] = -1;
+        let mut game = init_connect6_game(&cells).unwrap();
+
+        game.make_move(0, game.bot_mark);
+        assert_eq!(game.next_player(game.bot_mark), game.bot_mark);
+        game.make_move(1, game.bot_mark);
+        assert_eq!(game.next_player(game.bot_mark), game.p1_mark);
+    }
+
+    #[test]
+    fn connect6_inconsistent_marks_error() {
+        let mut cells = [0; 361];
+
+        assert!(init_connect6_game(&cells).is_ok());
+
+        cells[180] = -1;
+        assert!(init_connect6_game(&cells).is_ok());
+
+        cells[181] = 1;
+        assert!(matches!(
+            init_connect6_game(&cells),
+            Err(GameInitError::Inconsistent)
+        ));
+
+        cells[182] = 1;
+        cells[183] = -1;
+        cells[184] = -1;
+        assert!(init_connect6_game(&cells).is_ok());
+    }
+
+    #[test]
+    fn capture_inconsistent_pairs_error() {
+        let mut cells = [0; 81];
+        cells[40] = -1;
+
+        assert!(init_capture_game(&cells, [0, 0]).is_ok());
+        assert!(init_capture_game(&cells, [1, 1]).is_ok());
+        assert!(matches!(
+            init_capture_game(&cells, [0, 1]),
+            Err(GameInitError::Inconsistent)
+        ));
+        assert!(matches!(
+            init_capture_game(&cells, [6, 6]),
+            Err(GameInitError::Inconsistent)
+        ));
+    }
+
     #[test]
     fn board_cells_to_win_error() {
         // Test all board sizes from 3x3 to 15x15
@@ -602,6 +1381,7 @@ mod tests {
                 p1_mark: -1,
                 bot_mark: 1,
                 empty_mark: 0,
+                ..Default::default()
             };
             match Game::new(board, Level::Normal) {
                 Ok(_) => panic!("Game::new() returned Ok for invalid `cells_to_win` argument."),
@@ -614,7 +1394,7 @@ mod tests {
     #[test]
     fn init_new_game() {
         let (p1_mark, bot_mark, empty_mark) = (2, 11, 8);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
 
         let board = Board {
             cells: vec![8, 8, 11, 11, 8, 8, 2, 8, 2],
@@ -622,6 +1402,7 @@ mod tests {
             p1_mark,
             bot_mark,
             empty_mark,
+            ..Default::default()
         };
         let game = match Game::new(board, Level::Normal) {
             Ok(game) => game,
@@ -640,11 +1421,34 @@ mod tests {
         assert_eq!(game.orig_bot_mark, bot_mark);
         assert_eq!(game.orig_empty_mark, empty_mark);
 
-        assert_eq!(game.cells_offset, 3);
+        assert_eq!((game.rows, game.cols), (3, 3));
         assert_eq!(game.cells_to_win, cells_to_win as usize);
         assert_eq!(game.cells, correct_cells);
     }
 
+    #[test]
+    fn make_and_undo_move_state() {
+        let cells = [0, 0, 1, 0, -1, 0, 0, 0, 0];
+        let mut game = init_game(&cells, -1, 1, 0, 3);
+        let orig_hash = game.hash();
+
+        game.make_move(0, game.p1_mark);
+        game.make_move(8, game.bot_mark);
+        assert_ne!(game.hash(), orig_hash);
+        assert_eq!(game.hash(), game.zobrist.hash(&game.cells, game.empty_mark));
+        assert_eq!(
+            game.player_bits,
+            Game::player_bitboards(&game.cells, game.empty_mark)
+        );
+        assert_eq!(game.empty_cell_count(), 5);
+        assert_eq!(game.evaluation.total(), game.value_in_total());
+
+        game.undo_move(8);
+        game.undo_move(0);
+        assert_eq!(game.hash(), orig_hash);
+        assert_eq!(game.cells, cells);
+    }
+
     #[test]
     fn empty_cell_indices_3x3() {
         // For board X33 all empty indices should always be returned
@@ -654,7 +1458,7 @@ mod tests {
             [-1, 1, -1, 1, -1, 1, -1, 1, 0],
         ];
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
 
         for cells in &cells_collections {
             let true_empty_count = cells.iter().filter(|&cell| *cell == 0).count();
@@ -680,7 +1484,7 @@ mod tests {
             ],
         ];
         let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let mut game = init_game(
             &cells_collections[0],
@@ -721,13 +1525,71 @@ mod tests {
         assert_eq!(indices.len(), 14);
     }
 
+    #[test]
+    fn empty_cell_indices_gravity_6x7() {
+        #[rustfmt::skip]
+        let cells: [i8; 42] = [
+            0, 0, 0, 1, 0, 0, 0,
+            0, 0, 0, -1, 0, 0, 0,
+            0, 0, 0, 1, 0, 0, 0,
+            0, 0, 0, -1, 0, 0, 0,
+            0, 0, 0, 1, 0, 0, 0,
+            0, 1, -1, -1, 0, 0, 0,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(6),
+            cols: Some(7),
+            gravity: true,
+            ..Default::default()
+        };
+        let mut game = Game::new(board, Level::Normal).unwrap();
+
+        let mut indices = game.empty_cell_indices();
+        indices.sort_unstable();
+        assert_eq!(indices, vec![29, 30, 35, 39, 40, 41]);
+        assert!(game.is_playable(29));
+        assert!(!game.is_playable(22));
+    }
+
+    #[test]
+    fn empty_cell_indices_pruned_19x19() {
+        let mut cells = vec![0; 19 * 19];
+        for (i, idx) in [40, 60, 100, 180, 250, 300].into_iter().enumerate() {
+            cells[idx] = if i % 2 == 0 { -1 } else { 1 };
+        }
+        let mut game = init_game(&cells, -1, 1, 0, 5);
+
+        let indices = game.empty_cell_indices();
+        assert_eq!(indices.len(), MAX_CANDIDATE_MOVES);
+        assert!(indices.iter().all(|&idx| game.adjacent_cell_occupied(idx)));
+    }
+
+    #[test]
+    fn winner_in_last_row_19x19() {
+        let mut cells = vec![0; 19 * 19];
+        cells[356..].fill(1);
+        for idx in [0, 18, 20, 180, 342] {
+            cells[idx] = -1;
+        }
+        let game = init_game(&cells, -1, 1, 0, 5);
+
+        assert_eq!(game.winner(), game.bot_mark);
+        assert_eq!(game.winner_after_move(360), game.bot_mark);
+        assert_eq!(game.winner_after_move(342), game.empty_mark);
+    }
+
     #[test]
     fn adjacent_cell_occupied_5x5() {
         let cells: [i8; 25] = [
             1, 1, 1, 0, 1, 1, 1, 1, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0, 1, 1, 0, 1, 0, 1, 1,
         ];
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
         // Board (cells) is now inconsistent and cannot thus call Game::new directly
         let game = init_5x5_game_literal(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
 
@@ -770,7 +1632,7 @@ mod tests {
             [0, 0, 0, 0, 0, 0, 0, 1, 0],
         ];
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
         let correct_adjacent_count = [8, 3, 5, 5, 5];
 
         let it = cells_collections
@@ -802,7 +1664,7 @@ mod tests {
             ],
         ];
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
         let correct_adjacent_count = [3, 8, 8, 5];
 
         let it = cells_collections
@@ -820,7 +1682,7 @@ mod tests {
     #[test]
     fn row_winner_3x3() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
 
         let cells_collections: [[i8; 9]; 4] = [
             [-1, -1, 0, 1, 1, 1, 0, 0, 0],
@@ -839,7 +1701,7 @@ mod tests {
     #[test]
     fn col_winner_3x3() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
 
         let cells_collections: [[i8; 9]; 4] = [
             [-1, 1, 1, -1, 0, 0, -1, 0, 1],
@@ -857,7 +1719,7 @@ mod tests {
     #[test]
     fn diag_winner_3x3() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X33_CELLS_TO_WIN_MIN;
+        let cells_to_win = 3;
 
         let cells_collections: [[i8; 9]; 4] = [
             [1, -1, -1, 0, 1, 0, 0, -1, 1],
@@ -880,7 +1742,7 @@ mod tests {
     #[test]
     fn row_winner_5x5() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let cells_collections: [[i8; 25]; 5] = [
             [
@@ -910,7 +1772,7 @@ mod tests {
     #[test]
     fn col_winner_5x5() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let cells_collections: [[i8; 25]; 5] = [
             [
@@ -939,7 +1801,7 @@ mod tests {
     #[test]
     fn diag_winner_5x5() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let cells_collections: [[i8; 25]; 4] = [
             [
@@ -965,7 +1827,7 @@ mod tests {
     #[test]
     fn antidiag_winner_5x5() {
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let cells_collections: [[i8; 25]; 4] = [
             [
@@ -988,13 +1850,415 @@ mod tests {
         }
     }
 
+    #[test]
+    fn winner_after_move_5x5() {
+        let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
+        let cells_to_win = 4;
+
+        let cells_collections: [[i8; 25]; 4] = [
+            [
+                0, 1, 1, 1, 1, -1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            [
+                -1, -1, -1, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0,
+            ],
+            [
+                -1, 0, -1, -1, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1,
+            ],
+            [
+                0, 0, 0, 0, 1, 0, 0, 0, 1, -1, -1, -1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+        ];
+        // A cell of the winning line and an occupied cell outside of it
+        let win_move_indices = [4, 20, 12, 16];
+        let other_indices = [5, 0, 0, 9];
+
+        for (j, cells) in cells_collections.iter().enumerate() {
+            let game = init_game(cells, p1_mark, bot_mark, empty_mark, cells_to_win);
+
+            assert_eq!(game.winner(), game.p1_mark);
+            assert_eq!(
+                game.winner_after_move(win_move_indices[j]),
+                game.p1_mark,
+                "collection {j}"
+            );
+            assert_eq!(
+                game.winner_after_move(other_indices[j]),
+                game.empty_mark,
+                "collection {j}"
+            );
+        }
+    }
+
+    #[test]
+    fn winner_after_move_not_wrapping_rows() {
+        let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
+        // Two bot markers at the end of the 2nd row and two at the start of the 3rd
+        let cells: [i8; 25] = [
+            -1, -1, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 0, -1, -1, 0, 0, 0, 0, 0, 0,
+        ];
+        let game = init_game(&cells, p1_mark, bot_mark, empty_mark, 4);
+
+        for idx in [8, 9, 10, 11] {
+            assert_eq!(game.winner_after_move(idx), game.empty_mark);
+        }
+    }
+
+    fn init_torus_game(cells: &[i8], cells_to_win: u8) -> Game {
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            topology: Topology::Torus,
+            ..Default::default()
+        };
+        Game::new(board, Level::Normal).unwrap()
+    }
+
+    #[test]
+    fn winner_across_edges_torus_5x5() {
+        // Row wrapping from the end of the 2nd row to its start and antidiagonal
+        // wrapping from the top row to the bottom row
+        #[rustfmt::skip]
+        let cells_collections: [[i8; 25]; 2] = [
+            [
+                -1, -1, 0, 0, 0,
+                 1,  1, 0, 1, 1,
+                 0,  0, 0, 0, 0,
+                -1,  0, 0, 0, 0,
+                 0,  0, -1, 0, 0,
+            ],
+            [
+                 0,  1, 0, 0, 0,
+                 1, -1, 0, 0, 0,
+                 0, -1, 0, 0, 1,
+                 0, -1, 0, 1, 0,
+                 0,  0, 0, 0, -1,
+            ],
+        ];
+
+        for (i, cells) in cells_collections.iter().enumerate() {
+            let plane_game = init_5x5_game_literal(cells, -1, 1, 0, 4);
+            assert_eq!(
+                plane_game.winner(),
+                plane_game.empty_mark,
+                "collection {}",
+                i + 1
+            );
+
+            let game = init_torus_game(cells, 4);
+            assert_eq!(game.winner(), game.bot_mark, "collection {}", i + 1);
+            assert_eq!(
+                game.winner_after_move(5),
+                game.bot_mark,
+                "collection {}",
+                i + 1
+            );
+            assert_eq!(game.evaluation.total(), game.value_in_total());
+        }
+    }
+
+    #[test]
+    fn winner_after_move_whole_line_torus_4x4() {
+        let cells: [i8; 16] = [1, 1, 1, 1, -1, -1, 0, 0, -1, 0, 0, 0, -1, 0, 0, 0];
+        let game = init_torus_game(&cells, 4);
+
+        assert_eq!(game.winner_after_move(2), game.bot_mark);
+        assert_eq!(game.winner_after_move(4), game.empty_mark);
+    }
+
+    #[test]
+    fn adjacent_cell_occupied_torus_5x5() {
+        let mut cells = [0; 25];
+        cells[24] = -1;
+        let game = init_torus_game(&cells, 4);
+
+        for idx in [0, 4, 20, 18, 19, 23] {
+            assert!(game.adjacent_cell_occupied(idx), "index {idx}");
+        }
+        for idx in [1, 2, 12] {
+            assert!(!game.adjacent_cell_occupied(idx), "index {idx}");
+        }
+    }
+
+    #[test]
+    fn blocked_cell_breaks_lines_5x5() {
+        #[rustfmt::skip]
+        let cells: [i8; 25] = [
+            -1, -1, -1, 0, -1,
+             1,  1,  1, 2,  0,
+             0,  0,  0, 0,  0,
+             1,  1,  2, 1,  1,
+            -1,  0, -1, 0, -1,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            blocked_mark: Some(2),
+            ..Default::default()
+        };
+        let game = Game::new(board, Level::Normal).unwrap();
+
+        // Four bot markers on the 4th row are split by the blocked cell
+        assert_eq!(game.winner(), game.empty_mark);
+        assert_eq!(game.winner_after_move(18), game.empty_mark);
+        // No window of the 2nd row goes through the blocked cell
+        assert!(game.threat_cells(game.bot_mark, 1).is_empty());
+        assert_eq!(game.evaluation.total(), game.value_in_total());
+    }
+
+    #[test]
+    fn empty_cell_indices_markers_walled_in_5x5() {
+        #[rustfmt::skip]
+        let cells: [i8; 25] = [
+            -1, 2, 0, 2, -1,
+             2, 2, 0, 2,  2,
+             0, 0, 0, 0,  0,
+             0, 0, 0, 2,  2,
+             0, 0, 0, 2,  1,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 4,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            blocked_mark: Some(2),
+            ..Default::default()
+        };
+        let mut game = Game::new(board, Level::Normal).unwrap();
+
+        let indices = game.empty_cell_indices();
+        assert_eq!(
+            indices.len(),
+            cells.iter().filter(|&&cell| cell == 0).count()
+        );
+    }
+
+    #[test]
+    fn winner_rectangular_6x7() {
+        #[rustfmt::skip]
+        let cells: [i8; 42] = [
+            -1, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 1,
+            0, 0, 0, 0, 0, 0, 1,
+            0, 0, 0, 0, 0, 0, 1,
+            1, 1, 0, 0, 0, 0, 1,
+            -1, -1, -1, 0, -1, -1, 0,
+        ];
+        let game = init_rect_game(&cells, 6, 7, 4);
+
+        assert_eq!(game.winner(), game.bot_mark);
+        assert_eq!(game.winner_after_move(34), game.bot_mark);
+        assert_eq!(game.winner_after_move(35), game.empty_mark);
+        // Row 5 and the start of row 6 are consecutive indices but not a line
+        assert_eq!(game.winner_after_move(28), game.empty_mark);
+        assert_eq!(game.threat_cells(game.p1_mark, 1), vec![38]);
+    }
+
+    #[test]
+    fn overline_wins_only_freestyle_15x15() {
+        let mut cells = [0; 225];
+        // Six bot markers in the 8th row and six scattered p1 markers
+        cells[108..114].fill(1);
+        for idx in [0, 4, 30, 60, 150, 200] {
+            cells[idx] = -1;
+        }
+
+        let game = init_rule_game(&cells, 15, 15, 5, WinRule::Freestyle);
+        assert_eq!(game.winner(), game.bot_mark);
+        assert_eq!(game.winner_after_move(113), game.bot_mark);
+
+        let mut game = init_rule_game(&cells, 15, 15, 5, WinRule::Exact);
+        assert_eq!(game.winner(), game.empty_mark);
+        assert_eq!(game.winner_after_move(113), game.empty_mark);
+
+        // Exactly five in a row wins
+        game.undo_move(113);
+        assert_eq!(game.winner(), game.bot_mark);
+        assert_eq!(game.winner_after_move(108), game.bot_mark);
+    }
+
+    #[test]
+    fn blocked_line_wins_only_without_caro_15x15() {
+        let mut cells = [0; 225];
+        // Five bot markers in the 8th row blocked at both ends by p1
+        cells[108..113].fill(1);
+        for idx in [107, 113, 30, 60, 150, 200] {
+            cells[idx] = -1;
+        }
+
+        let game = init_rule_game(&cells, 15, 15, 5, WinRule::Freestyle);
+        assert_eq!(game.winner(), game.bot_mark);
+        assert_eq!(game.winner_after_move(110), game.bot_mark);
+
+        let game = init_rule_game(&cells, 15, 15, 5, WinRule::Caro);
+        assert_eq!(game.winner(), game.empty_mark);
+        assert_eq!(game.winner_after_move(110), game.empty_mark);
+
+        // Board edge doesn't block, five in the end of the row wins
+        cells[105..120].copy_from_slice(&[0, 0, -1, 0, 0, 0, 0, 0, 0, -1, 1, 1, 1, 1, 1]);
+        let game = init_rule_game(&cells, 15, 15, 5, WinRule::Caro);
+        assert_eq!(game.winner(), game.bot_mark);
+        assert_eq!(game.winner_after_move(119), game.bot_mark);
+    }
+
+    #[test]
+    fn renju_forbidden_moves_15x15() {
+        // Bot is black having made the first move, center cell 112 is tested
+        let white = [0, 14, 210, 224, 30, 44, 180];
+        let test_cases: [(&[usize], bool); 5] = [
+            // Double three
+            (&[110, 111, 82, 97], true),
+            // Double four
+            (&[109, 110, 111, 67, 82, 97], true),
+            // Overline
+            (&[108, 109, 110, 111, 113], true),
+            // Five with a four
+            (&[108, 109, 110, 111, 82, 97, 67], false),
+            // Single three and a two
+            (&[110, 111, 97], false),
+        ];
+
+        for (black, forbidden) in test_cases {
+            let mut cells = [0; 225];
+            for &idx in black.iter() {
+                cells[idx] = 1;
+            }
+            for &idx in white.iter().take(black.len()) {
+                cells[idx] = -1;
+            }
+            let mut game = init_rule_game(&cells, 15, 15, 5, WinRule::Renju);
+
+            assert_eq!(game.is_forbidden(112), forbidden, "black {:?}", black);
+            assert_eq!(!game.empty_cell_indices().contains(&112), forbidden);
+        }
+    }
+
+    #[test]
+    fn renju_overline_wins_only_for_white_15x15() {
+        let mut cells = [0; 225];
+        cells[108..114].fill(-1);
+        for idx in [0, 14, 210, 224, 30, 44] {
+            cells[idx] = 1;
+        }
+
+        // Black p1 has made the first move if it has one more marker
+        let game = init_rule_game(&cells, 15, 15, 5, WinRule::Renju);
+        assert_eq!(game.winner(), game.p1_mark);
+
+        cells[150] = -1;
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 5,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            win_rule: WinRule::Renju,
+            last_move: Some(150),
+            ..Default::default()
+        };
+        let game = Game::new(board, Level::Normal).unwrap();
+        assert_eq!(game.winner(), game.empty_mark);
+    }
+
+    #[test]
+    fn renju_forbidden_latest_move_error() {
+        let mut cells = [0; 225];
+        // Black p1 has made a double three to cell 112
+        for idx in [110, 111, 82, 97, 112] {
+            cells[idx] = -1;
+        }
+        for idx in [0, 14, 210, 224] {
+            cells[idx] = 1;
+        }
+        let board = |last_move: Option<u16>| Board {
+            cells: cells.to_vec(),
+            cells_to_win: 5,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            win_rule: WinRule::Renju,
+            last_move,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            Game::new(board(Some(112)), Level::Normal),
+            Err(GameInitError::Forbidden)
+        ));
+        assert!(Game::new(board(Some(110)), Level::Normal).is_ok());
+        // Latest move must be known and one of p1's markers
+        for last_move in [None, Some(0), Some(1)] {
+            assert!(matches!(
+                Game::new(board(last_move), Level::Normal),
+                Err(GameInitError::Inconsistent)
+            ));
+        }
+    }
+
+    #[test]
+    fn threat_cells_exact_rule_15x15() {
+        let mut cells = [0; 225];
+        // Bot markers in the 8th row at columns 1-4 and 6
+        for idx in [106, 107, 108, 109, 111] {
+            cells[idx] = 1;
+        }
+        for idx in [0, 4, 30, 60, 150, 200] {
+            cells[idx] = -1;
+        }
+
+        let game = init_rule_game(&cells, 15, 15, 5, WinRule::Freestyle);
+        assert_eq!(game.threat_cells(game.bot_mark, 1), vec![105, 110]);
+
+        let game = init_rule_game(&cells, 15, 15, 5, WinRule::Exact);
+        assert_eq!(game.threat_cells(game.bot_mark, 1), vec![105]);
+    }
+
+    #[test]
+    fn rectangular_board_3x4() {
+        #[rustfmt::skip]
+        let cells: [i8; 12] = [
+            1, 0, 0, 0,
+            0, 0, 0, 0,
+            0, 0, 0, -1,
+        ];
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win: 3,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            rows: Some(3),
+            cols: Some(4),
+            ..Default::default()
+        };
+        let board_table = BoardTable::from_json(
+            r#"[{"rows": 3, "cols": 4, "cells_to_win_min": 3, "cells_to_win_max": 3, "easy_max_depth": 1, "normal_max_depth": 9}]"#,
+        )
+        .unwrap();
+        let game = Game::with_board_table(board, Level::Normal, &board_table).unwrap();
+
+        assert_eq!((game.rows(), game.cols()), (3, 4));
+        assert!(game.adjacent_cell_occupied(5));
+        assert!(game.adjacent_cell_occupied(7));
+        assert!(!game.adjacent_cell_occupied(3));
+        assert!(!game.adjacent_cell_occupied(8));
+        assert_eq!(game.to_string(), "x - - - \n- - - - \n- - - o \n\n");
+    }
+
     #[test]
     fn value_in_rows() {
         let cells: [i8; 25] = [
             0, 0, 1, 1, 1, 1, -1, 1, 0, 0, 1, -1, -1, 1, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0,
         ];
         let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
         // Board (cells) is now inconsistent and cannot thus call Game::new
         let game = init_5x5_game_literal(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
         // First and last row bring value (cells' sum * value)
@@ -1011,7 +2275,7 @@ mod tests {
             0, 0, 0, 0, 1, 1, 1, 0, 0, 1, 0, 1, 1, 0, 0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 0,
         ];
         let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let game = init_5x5_game_literal(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
         // 1st, 2nd, 3rd and 5th column bring value
@@ -1027,7 +2291,7 @@ mod tests {
             0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 1,
         ];
         let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let game = init_5x5_game_literal(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
         // 2 ONE_TO_WIN and 2 TWO_TO_WIN cases in total
@@ -1042,7 +2306,7 @@ mod tests {
             0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0,
         ];
         let (p1_mark, bot_mark, empty_mark) = (-1, 1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let game = init_5x5_game_literal(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
         // 1 ONE_TO_WIN and 3 TWO_TO_WIN cases in total
@@ -1051,18 +2315,54 @@ mod tests {
         assert_eq!(game.value_in_diags(true), correct_value);
     }
 
+    #[test]
+    fn incremental_value_matches_full_evaluation() {
+        for (rows, cols, cells_to_win, win_rule) in [
+            (5, 5, 4, WinRule::Freestyle),
+            (9, 9, 5, WinRule::Freestyle),
+            (6, 7, 4, WinRule::Freestyle),
+            (15, 10, 5, WinRule::Freestyle),
+            (15, 15, 5, WinRule::Freestyle),
+            (6, 7, 4, WinRule::Exact),
+            (15, 15, 5, WinRule::Exact),
+            (9, 9, 5, WinRule::Caro),
+            (15, 15, 5, WinRule::Renju),
+        ] {
+            let cells = vec![0; rows as usize * cols as usize];
+            let mut game = init_rule_game(&cells, rows, cols, cells_to_win, win_rule);
+            assert_eq!((game.rows(), game.cols()), (rows as usize, cols as usize));
+
+            let mut indices: Vec<usize> = (0..cells.len()).collect();
+            indices.shuffle(&mut rand::thread_rng());
+
+            for (i, &idx) in indices.iter().take(cells.len() / 2).enumerate() {
+                let player = if i % 2 == 0 {
+                    game.bot_mark
+                } else {
+                    game.p1_mark
+                };
+                game.make_move(idx, player);
+                assert_eq!(game.evaluation.total(), game.value_in_total());
+            }
+            for &idx in indices.iter().take(cells.len() / 2).rev() {
+                game.undo_move(idx);
+                assert_eq!(game.evaluation.total(), game.value_in_total());
+            }
+        }
+    }
+
     #[test]
     fn value_penalty_opposite_player() {
         let cells: [i8; 25] = [
             0, -1, -1, -1, 0, 0, 0, 0, 0, 0, -1, 1, -1, -1, 0, 0, 0, 0, 0, 0, -1, 0, -1, 0, 1,
         ];
         let (p1_mark, bot_mark, empty_mark) = (1, -1, 0);
-        let cells_to_win = X55_CELLS_TO_WIN_MIN;
+        let cells_to_win = 4;
 
         let game = init_5x5_game_literal(&cells, p1_mark, bot_mark, empty_mark, cells_to_win);
 
         let correct_value_first_row = 2 * -3 * ONE_TO_WIN_VALUE * OPPONENT_PENALTY_MULTIPLIER;
-        let correct_value_last_row = 1 * -2 * TWO_TO_WIN_VALUE * OPPONENT_PENALTY_MULTIPLIER;
+        let correct_value_last_row = -2 * TWO_TO_WIN_VALUE * OPPONENT_PENALTY_MULTIPLIER;
         let correct_value = correct_value_first_row + correct_value_last_row;
 
         assert_eq!(game.value_in_rows(), correct_value);
@@ -1,12 +1,21 @@
 use actix_web::{self, web, HttpResponse, Responder};
+use serde::Serialize;
+use std::{cmp, time::Duration};
 
 use crate::{
     bot::Bot,
-    conf::GameInitError,
+    conf::{BoardTable, GameInitError},
     game::Game,
-    models::{Board, LevelQuery},
+    models::{Board, LevelQuery, OpeningBoard},
+    slots::{SearchConf, SearchSlots},
 };
 
+// Upper limit for a time budget requested by a client
+const MAX_TIME_BUDGET_MS: u64 = 10000;
+
+// Upper limit for Monte Carlo tree search iterations requested by a client
+const MAX_MCTS_ITERATIONS: u32 = 100000;
+
 pub async fn hello() -> impl Responder {
     HttpResponse::Ok()
 }
@@ -14,29 +23,142 @@ pub async fn hello() -> impl Responder {
 pub async fn next_move(
     game_board: web::Json<Board>,
     game_level: web::Query<LevelQuery>,
+    search_conf: web::Data<SearchConf>,
+    search_slots: web::Data<SearchSlots>,
+    board_table: web::Data<BoardTable>,
 ) -> impl Responder {
     let board = game_board.into_inner();
-    let level = game_level.into_inner().level;
 
-    let game = match Game::new(board, level) {
+    match init_game(board, game_level.into_inner(), &search_conf, &board_table) {
+        Ok(game) => search_bot_move(game, search_slots).await,
+        Err(response) => response,
+    }
+}
+
+/// Suggest the next move for player 1 by searching it as if the bot was player 1.
+pub async fn hint(
+    game_board: web::Json<Board>,
+    game_level: web::Query<LevelQuery>,
+    search_conf: web::Data<SearchConf>,
+    search_slots: web::Data<SearchSlots>,
+    board_table: web::Data<BoardTable>,
+) -> impl Responder {
+    let board = game_board.into_inner().players_swapped();
+
+    match init_game(board, game_level.into_inner(), &search_conf, &board_table) {
+        Ok(game) => search_bot_move(game, search_slots).await,
+        Err(response) => response,
+    }
+}
+
+/// Score every candidate move of the bot, e.g. for showing a heatmap of the board.
+pub async fn analyze(
+    game_board: web::Json<Board>,
+    game_level: web::Query<LevelQuery>,
+    search_conf: web::Data<SearchConf>,
+    search_slots: web::Data<SearchSlots>,
+    board_table: web::Data<BoardTable>,
+) -> impl Responder {
+    let board = game_board.into_inner();
+
+    match init_game(board, game_level.into_inner(), &search_conf, &board_table) {
+        Ok(game) => run_search(move || Bot::analyze(game), search_slots).await,
+        Err(response) => response,
+    }
+}
+
+/// Play the bot's part of the opening phase of a game using an opening protocol.
+pub async fn opening(
+    game_board: web::Json<OpeningBoard>,
+    game_level: web::Query<LevelQuery>,
+    search_conf: web::Data<SearchConf>,
+    search_slots: web::Data<SearchSlots>,
+    board_table: web::Data<BoardTable>,
+) -> impl Responder {
+    let opening_board = game_board.into_inner();
+    let opening = opening_board.opening;
+
+    if !opening
+        .phase_stone_counts()
+        .contains(&opening_board.stones_count())
+    {
+        return HttpResponse::BadRequest().body("Board is not in a phase of the opening.");
+    }
+
+    let board = Board::from(opening_board);
+
+    match init_game(board, game_level.into_inner(), &search_conf, &board_table) {
+        Ok(game) => run_search(move || Bot::opening_move(game, opening), search_slots).await,
+        Err(response) => response,
+    }
+}
+
+fn init_game(
+    board: Board,
+    query: LevelQuery,
+    search_conf: &SearchConf,
+    board_table: &BoardTable,
+) -> Result<Game, HttpResponse> {
+    let mut game = match Game::with_board_table(board, query.level, board_table) {
         Ok(game) => game,
         Err(GameInitError::Size) => {
-            return HttpResponse::BadRequest().body("Board size is unaccepted.");
+            return Err(HttpResponse::BadRequest().body("Board size is unaccepted."));
         }
         Err(GameInitError::Marks) => {
-            return HttpResponse::BadRequest().body("Player markers in the board are unaccepted.");
+            return Err(
+                HttpResponse::BadRequest().body("Player markers in the board are unaccepted.")
+            );
         }
         Err(GameInitError::Inconsistent) => {
-            return HttpResponse::BadRequest().body("Board state is inconsistent.");
+            return Err(HttpResponse::BadRequest().body("Board state is inconsistent."));
         }
         Err(GameInitError::CellsToWin) => {
-            return HttpResponse::BadRequest().body("Cells to win value is unaccepted.");
+            return Err(HttpResponse::BadRequest().body("Cells to win value is unaccepted."));
+        }
+        Err(GameInitError::Forbidden) => {
+            return Err(HttpResponse::BadRequest().body("Latest move is forbidden."));
         }
     };
 
-    let bot_next_move = Bot::next_move(game);
+    game.time_budget = query
+        .time_ms
+        .map(|time_ms| Duration::from_millis(cmp::min(time_ms, MAX_TIME_BUDGET_MS)));
+    game.engine = query.engine;
+    game.search_threads = search_conf.threads;
+    game.verbose = query.verbose;
+
+    if let Some(iterations) = query.iterations {
+        game.mcts_iterations = cmp::min(iterations, MAX_MCTS_ITERATIONS);
+    }
+
+    Ok(game)
+}
+
+async fn search_bot_move(game: Game, search_slots: web::Data<SearchSlots>) -> HttpResponse {
+    run_search(move || Bot::next_move(game), search_slots).await
+}
+
+async fn run_search<T, F>(search: F, search_slots: web::Data<SearchSlots>) -> HttpResponse
+where
+    T: Serialize + Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let Some(search_slot) = SearchSlots::try_acquire(search_slots.into_inner()) else {
+        return HttpResponse::ServiceUnavailable().body("Server is busy, try again later.");
+    };
+
+    // Search is CPU bound and must not block the async worker
+    let result = web::block(move || {
+        let result = search();
+        drop(search_slot);
+        result
+    })
+    .await;
 
-    HttpResponse::Ok().json(bot_next_move)
+    match result {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(_) => HttpResponse::InternalServerError().finish(),
+    }
 }
 
 pub async fn robots_txt() -> impl Responder {
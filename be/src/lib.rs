@@ -1,7 +1,15 @@
+mod bitboard;
 mod bot;
 mod conf;
+mod evaluation;
 mod first_move;
 mod game;
+mod mcts;
 mod models;
+mod threats;
+mod transposition;
 
-pub use {bot::Bot, game::Game, models::Board, models::Level};
+pub use {
+    bot::Bot, conf::BoardTable, game::Game, models::Board, models::Engine, models::Level,
+    models::OpeningRule, models::Topology, models::WinRule,
+};
@@ -0,0 +1,291 @@
+use rand::Rng;
+use std::{
+    cmp,
+    time::{Duration, Instant},
+};
+
+use crate::game::Game;
+
+// Exploration constant of the UCT formula
+const EXPLORATION: f32 = 1.4;
+
+// Rollouts are cut after this many moves and the result estimated by the heuristic
+const ROLLOUT_MAX_MOVES: usize = 30;
+
+// Rollout moves are picked randomly from this many best candidates
+const ROLLOUT_CANDIDATES: usize = 4;
+
+// Heuristic board value at which the estimated win probability is about 73 %
+const HEURISTIC_SCALE: f32 = 500.0;
+
+struct Node {
+    // Move leading to this node and the player who made it
+    cell: Option<usize>,
+    player: i8,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    // Moves not yet expanded, the best candidate last
+    untried: Vec<usize>,
+    terminal: bool,
+    visits: u32,
+    // Sum of the rewards from the perspective of `player`
+    reward: f32,
+}
+
+/// Monte Carlo tree search with the UCT selection rule.
+///
+/// Every iteration selects a path down the tree, expands one new node, plays
+/// a rollout from there and propagates its result back up the path. Rollouts
+/// are guided by the heuristic: immediate wins and blocks are always played and
+/// other moves are picked among the best valued candidates. Long rollouts are
+/// cut and their result estimated from the heuristic board value.
+pub struct Mcts {
+    nodes: Vec<Node>,
+}
+
+impl Mcts {
+    /// Best move for the bot after `iterations` iterations or when `time_budget` runs out.
+    pub fn best_move(
+        game: &mut Game,
+        iterations: u32,
+        time_budget: Option<Duration>,
+    ) -> Option<usize> {
+        let deadline = time_budget.map(|time_budget| Instant::now() + time_budget);
+        let root_terminal = game.winner() != game.empty_mark;
+
+        let mut mcts = Mcts {
+            nodes: vec![Self::new_node(
+                game,
+                None,
+                game.p1_mark,
+                None,
+                root_terminal,
+            )],
+        };
+
+        for _ in 0..cmp::max(iterations, 1) {
+            mcts.iterate(game);
+
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+        }
+
+        mcts.nodes[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| mcts.nodes[child].visits)
+            .and_then(|&child| mcts.nodes[child].cell)
+    }
+
+    fn new_node(
+        game: &mut Game,
+        cell: Option<usize>,
+        player: i8,
+        parent: Option<usize>,
+        terminal: bool,
+    ) -> Node {
+        let mut untried = if terminal {
+            Vec::new()
+        } else {
+            game.empty_cell_indices()
+        };
+        untried.reverse();
+
+        Node {
+            cell,
+            player,
+            parent,
+            children: Vec::new(),
+            terminal: terminal || untried.is_empty(),
+            untried,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+
+    fn iterate(&mut self, game: &mut Game) {
+        let mut node = 0;
+        let mut path_moves = Vec::new();
+
+        // Selection
+        while !self.nodes[node].terminal && self.nodes[node].untried.is_empty() {
+            node = self.uct_child(node);
+            let (cell, player) = (self.nodes[node].cell.unwrap(), self.nodes[node].player);
+
+            game.make_move(cell, player);
+            path_moves.push(cell);
+        }
+
+        // Expansion
+        if let Some(cell) = self.nodes[node].untried.pop() {
+            let player = game.next_player(self.nodes[node].player);
+
+            game.make_move(cell, player);
+            path_moves.push(cell);
+
+            let terminal = game.winner_after_move(cell) != game.empty_mark;
+            let child = Self::new_node(game, Some(cell), player, Some(node), terminal);
+
+            self.nodes.push(child);
+            let child_idx = self.nodes.len() - 1;
+            self.nodes[node].children.push(child_idx);
+            node = child_idx;
+        }
+
+        let bot_reward = Self::rollout(game, self.nodes[node].player);
+
+        for &cell in path_moves.iter().rev() {
+            game.undo_move(cell);
+        }
+
+        // Backpropagation
+        let mut next = Some(node);
+        while let Some(idx) = next {
+            let node = &mut self.nodes[idx];
+
+            node.visits += 1;
+            node.reward += if node.player == game.bot_mark {
+                bot_reward
+            } else {
+                1.0 - bot_reward
+            };
+            next = node.parent;
+        }
+    }
+
+    fn uct_child(&self, node: usize) -> usize {
+        let log_visits = (self.nodes[node].visits as f32).ln();
+
+        let uct_value = |child: usize| {
+            let child = &self.nodes[child];
+            let visits = child.visits as f32;
+
+            child.reward / visits + EXPLORATION * (log_visits / visits).sqrt()
+        };
+
+        *self.nodes[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct_value(a).total_cmp(&uct_value(b)))
+            .expect("nonterminal node with all moves tried has children")
+    }
+
+    /// Play the game from the current position and return the bot's reward.
+    ///
+    /// Reward is 1 for a bot win, 0 for a loss and 0.5 for a draw. Game is
+    /// restored to the original position before returning.
+    fn rollout(game: &mut Game, last_player: i8) -> f32 {
+        let mut rng = rand::thread_rng();
+        let mut rollout_moves = Vec::new();
+        let mut player = last_player;
+
+        let mut winner = match game.last_move() {
+            Some(last_move) => game.winner_after_move(last_move),
+            None => game.winner(),
+        };
+
+        while winner == game.empty_mark && rollout_moves.len() < ROLLOUT_MAX_MOVES {
+            player = game.next_player(player);
+
+            // Completing a line loses a misère game, so threats are not followed
+            let threats = if game.misere {
+                (None, None)
+            } else {
+                (
+                    game.threat_cells(player, 1).first().copied(),
+                    game.threat_cells(game.opponent(player), 1).first().copied(),
+                )
+            };
+
+            let cell = match threats {
+                (Some(win_cell), _) => win_cell,
+                (None, Some(block_cell)) => block_cell,
+                (None, None) => {
+                    // Candidates are sorted by their value for the bot
+                    let mut candidates = game.empty_cell_indices();
+                    if candidates.is_empty() {
+                        break;
+                    }
+                    if player != game.bot_mark {
+                        candidates.reverse();
+                    }
+                    let top = cmp::min(candidates.len(), ROLLOUT_CANDIDATES);
+                    candidates[rng.gen_range(0..top)]
+                }
+            };
+
+            game.make_move(cell, player);
+            rollout_moves.push(cell);
+            winner = game.winner_after_move(cell);
+        }
+
+        let winner = game.game_winner(winner);
+        let reward = if winner == game.bot_mark {
+            1.0
+        } else if winner == game.p1_mark {
+            0.0
+        } else {
+            let value = game.heuristic_game_value(winner, 0) as f32;
+            1.0 / (1.0 + (-value / HEURISTIC_SCALE).exp())
+        };
+
+        for &cell in rollout_moves.iter().rev() {
+            game.undo_move(cell);
+        }
+        reward
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Board, Level};
+
+    fn init_game(cells: &[i8], cells_to_win: u8) -> Game {
+        let board = Board {
+            cells: cells.to_vec(),
+            cells_to_win,
+            p1_mark: -1,
+            bot_mark: 1,
+            empty_mark: 0,
+            ..Default::default()
+        };
+        Game::new(board, Level::Normal).unwrap()
+    }
+
+    #[test]
+    fn mcts_make_win_move_3x3() {
+        let cells = [1, -1, 0, 0, 1, -1, 0, 0, 0];
+        let mut game = init_game(&cells, 3);
+
+        assert_eq!(Mcts::best_move(&mut game, 1000, None), Some(8));
+        assert_eq!(game.cells, cells);
+    }
+
+    #[test]
+    fn mcts_prevent_win_move_3x3() {
+        let cells = [-1, 0, 0, 0, -1, 1, 0, 0, 0];
+        let mut game = init_game(&cells, 3);
+
+        assert_eq!(Mcts::best_move(&mut game, 1000, None), Some(8));
+    }
+
+    #[test]
+    fn mcts_make_win_move_5x5() {
+        let cells = [
+            1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, -1, 0, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let mut game = init_game(&cells, 4);
+
+        assert_eq!(Mcts::best_move(&mut game, 1000, None), Some(3));
+    }
+
+    #[test]
+    fn mcts_game_already_over() {
+        let cells = [1, 1, 1, -1, -1, 0, -1, 0, 0];
+        let mut game = init_game(&cells, 3);
+
+        assert_eq!(Mcts::best_move(&mut game, 100, None), None);
+    }
+}
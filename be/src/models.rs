@@ -5,7 +5,10 @@ use serde::{Deserialize, Serialize};
 ///
 /// Length of `cells` must be equal to the game board size k x k
 /// such that first k cell values represent the first board row,
-/// next k values the second row and etc. `cells_to_win` determines
+/// next k values the second row and etc. For an m x n board with
+/// m rows and n columns, `rows` and `cols` must be given and each
+/// row has n cell values. Without them the board is taken to be
+/// square. `cells_to_win` determines
 /// how many consecutive cells of a same marker value are required
 /// to win the game.
 ///
@@ -15,6 +18,10 @@ use serde::{Deserialize, Serialize};
 /// contain equal amount of player marks or one more for `p1_mark`
 /// as the bot player plays next.
 ///
+/// Optional `blocked_mark` marks neutral blocked cells which neither player
+/// can use. Blocked cells break any line going through them and they are
+/// not counted as markers of either player.
+///
 /// For example, consider a 3x3 3-in-a-row game where the player
 /// using the `p1_mark` marker has made the first move of the game to
 /// the center cell (2nd row and column). Using -1 as this marker value,
@@ -22,28 +29,124 @@ use serde::{Deserialize, Serialize};
 /// situation correctly with 0 values representing the `empty_mark`.
 /// Of course, in this case the bot player must be play next using its
 /// `bot_mark` marker.
+///
+/// `win_rule` decides which lines of `cells_to_win` markers win, see `WinRule`.
+/// `last_move` gives the index of the latest move, made by the opponent of
+/// the player to move, so by player 1 when asking for the bot's move and by
+/// the bot when asking for a hint. In `renju` games it's required when that
+/// opponent has made the first move of the game, as this move must not be
+/// forbidden.
+///
+/// If `captures` is true, a move flanking exactly two markers of the
+/// opponent captures them like in Pente, and `p1_captured_pairs` and
+/// `bot_captured_pairs` give the numbers of pairs the players have captured
+/// so far. Captured markers are removed from `cells`. Capturing five pairs
+/// also wins the game.
+///
+/// `topology` decides whether the lines end at the board edges, see `Topology`.
+///
+/// If `misere` is true, the player making `cells_to_win` in a row loses
+/// the game instead of winning it, like in reverse tic-tac-toe. Misère games
+/// can't be played with `captures`.
+///
+/// If `connect6` is true, each turn after the first one places two markers
+/// like in Connect6, usually played with six cells to win. `Cells` must then
+/// contain one more `p1_mark` than `bot_mark` or no markers at all.
+///
+/// If `gravity` is true, markers drop to the lowest empty cell of a column
+/// like in Connect Four. Every marker must then be on the bottom row or on
+/// top of another marker.
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Clone, Debug, Default)]
 pub struct Board {
     pub cells: Vec<i8>,
     pub cells_to_win: u8,
     pub p1_mark: i8,
     pub bot_mark: i8,
     pub empty_mark: i8,
+    #[serde(default)]
+    pub blocked_mark: Option<i8>,
+    #[serde(default)]
+    pub rows: Option<u8>,
+    #[serde(default)]
+    pub cols: Option<u8>,
+    #[serde(default)]
+    pub win_rule: WinRule,
+    #[serde(default)]
+    pub last_move: Option<u16>,
+    #[serde(default)]
+    pub captures: bool,
+    #[serde(default)]
+    pub p1_captured_pairs: u8,
+    #[serde(default)]
+    pub bot_captured_pairs: u8,
+    #[serde(default)]
+    pub topology: Topology,
+    #[serde(default)]
+    pub misere: bool,
+    #[serde(default)]
+    pub connect6: bool,
+    #[serde(default)]
+    pub gravity: bool,
 }
 
-impl From<web::Json<Board>> for Board {
-    fn from(board: web::Json<Board>) -> Self {
+impl Board {
+    /// Same board with the roles of the players swapped.
+    ///
+    /// Bot playing on the returned board plays for player 1, which makes it
+    /// possible to suggest moves for player 1 when it's player 1's turn.
+    /// `last_move` is kept, so it must be the bot's latest move.
+    pub fn players_swapped(self) -> Self {
         Board {
-            cells: board.cells.clone(),
-            cells_to_win: board.cells_to_win,
-            p1_mark: board.p1_mark,
-            bot_mark: board.bot_mark,
-            empty_mark: board.empty_mark,
+            p1_mark: self.bot_mark,
+            bot_mark: self.p1_mark,
+            p1_captured_pairs: self.bot_captured_pairs,
+            bot_captured_pairs: self.p1_captured_pairs,
+            ..self
         }
     }
 }
 
+impl From<web::Json<Board>> for Board {
+    fn from(board: web::Json<Board>) -> Self {
+        board.into_inner()
+    }
+}
+
+/// Rule for the lines of `cells_to_win` markers winning the game.
+///
+/// With value `freestyle` (the default) also longer lines (overlines) win.
+/// With value `exact` only lines of exactly `cells_to_win` markers win,
+/// like in standard gomoku. With value `caro` a line doesn't win if the
+/// opponent has markers at both ends of it, like in Vietnamese Caro.
+///
+/// With value `renju` the player making the first move (black) wins only
+/// with lines of exactly `cells_to_win` markers and is not allowed to make
+/// moves creating an overline, two fours or two open threes at once.
+/// The other player wins like in `freestyle`.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WinRule {
+    #[default]
+    Freestyle,
+    Exact,
+    Caro,
+    Renju,
+}
+
+/// Shape of the board surface.
+///
+/// With value `plane` (the default) the lines end at the board edges. With
+/// value `torus` rows, columns and diagonals wrap across the edges, e.g. the
+/// last cell of a row is followed by the first cell of the same row.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Topology {
+    #[default]
+    Plane,
+    Torus,
+}
+
 /// URL query string parameter indicating difficulty of a game.
 ///
 /// With value `Normal` a game should usually end up to a draw if played
@@ -59,29 +162,214 @@ pub enum Level {
     Normal,
 }
 
+/// URL query string parameter selecting the search algorithm of the bot.
+///
+/// Value `minimax` (the default) uses minimax search with alpha-beta pruning
+/// and `mcts` Monte Carlo tree search.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    #[default]
+    Minimax,
+    Mcts,
+}
+
+/// URL query string parameters of the bot's next move request.
+///
+/// Optional `time_ms` sets a time budget in milliseconds for the bot's search.
+/// Without it the search depth is determined solely by the board size and `level`.
+/// Optional `iterations` limits the number of iterations of the `mcts` engine.
+/// With `verbose` set to true the response includes statistics of the search.
 #[allow(dead_code)]
 #[derive(Deserialize, Clone, Debug)]
 pub struct LevelQuery {
     pub level: Level,
+    pub time_ms: Option<u64>,
+    #[serde(default)]
+    pub engine: Engine,
+    pub iterations: Option<u32>,
+    #[serde(default)]
+    pub verbose: bool,
 }
 
 /// Bot player's next move and status of the game after this move.
 ///
 /// `next` is the board index of the bot's next move. This index must
-/// be interpreted such that values 0-(k-1) represent the first row of
-/// the k x k board, k-(2k-1) the second row and etc. If `next_is_valid`
+/// be interpreted such that values 0-(n-1) represent the first row of
+/// the m x n board, n-(2n-1) the second row and etc. If `next_is_valid`
 /// is true, this index is valid and can be played and updated to the
-/// board. If not true, the index is just a garbage value (u8::MAX)
+/// board. If not true, the index is just a garbage value (u16::MAX)
 /// and cannot be played. Also, in this case, `game_over` is always true.
 /// If `game_over` is true, `winner` is one of the player marks representing
-/// the winner (or empty marker if the game ended to a draw). If both
+/// the winner (or empty marker if the game ended to a draw). In misère games
+/// the winner is the opponent of the player who made the line. If both
 /// `next_is_valid` and `game_over` are true, this bot player's move
 /// `next` is the last one and ends the game.
+///
+/// In Connect6 games `moves` lists the board indices of all markers placed by
+/// the bot in its turn, `next` being the first of them. `game_over` and `winner`
+/// then give the status of the game after the whole turn.
+///
+/// `column` is the column of the move, included only for gravity games.
+/// `captured` lists the board indices of the markers captured by the move,
+/// included only if the move captured any.
+/// `stats` is included only for games with verbose output enabled.
 
 #[derive(Serialize, Clone, Debug)]
 pub struct BotMove {
-    pub next: u8,
+    pub next: u16,
     pub next_is_valid: bool,
     pub game_over: bool,
     pub winner: i8,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub moves: Vec<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u8>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub captured: Vec<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SearchStats>,
+}
+
+/// Statistics of the search that found the bot's next move.
+///
+/// `principal_variation` is the sequence of moves expected to follow, starting
+/// with the bot's next move and alternating between the bot and player 1.
+/// `score` is the minimax value of the next move, larger values being better
+/// for the bot, and `depth` the depth of the last completed search iteration.
+/// Both are null if the move wasn't found by minimax, e.g. for a random or
+/// forced move. `nodes` counts the positions visited by minimax and
+/// `elapsed_ms` is the total time used for the move.
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct SearchStats {
+    pub principal_variation: Vec<u16>,
+    pub score: Option<i32>,
+    pub depth: Option<u32>,
+    pub nodes: u64,
+    pub elapsed_ms: f64,
+}
+
+/// Scores of the bot's playable moves and the expected continuation of the game.
+///
+/// `scores` has a value for every board cell, indexed like the `cells` of a board.
+/// A value is the minimax value of the bot's move to the cell, larger values being
+/// better for the bot. Every cell the bot can play has a value, and only occupied
+/// and blocked cells, unsupported cells of gravity games and cells forbidden for
+/// the bot in renju have null values. All values are null if the game is already
+/// over. `best` is the board index of the best move, or null if the game is already
+/// over. `principal_variation` is the sequence of moves expected to follow, starting
+/// with `best` and alternating between the bot and player 1.
+#[derive(Serialize, Clone, Debug)]
+pub struct Analysis {
+    pub scores: Vec<Option<i32>>,
+    pub best: Option<u16>,
+    pub principal_variation: Vec<u16>,
+}
+
+/// Opening protocol of a game, used e.g. to balance 15x15 5-in-a-row.
+///
+/// With `swap` the first player places three stones, two black and one white,
+/// after which the second player chooses its colour. With `swap2` the second
+/// player may instead place two more stones, one of each colour, and leave the
+/// choice of colours to the first player. White moves next after the opening.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OpeningRule {
+    Swap,
+    Swap2,
+}
+
+impl OpeningRule {
+    /// Numbers of stones on the board in the phases the bot acts in.
+    pub fn phase_stone_counts(&self) -> &'static [usize] {
+        match self {
+            OpeningRule::Swap => &[0, 3],
+            OpeningRule::Swap2 => &[0, 3, 5],
+        }
+    }
+}
+
+/// Board in the opening phase of a game using opening protocol `opening`.
+///
+/// Unlike in `Board`, stones are given by their colour, `black_mark` for black
+/// and `white_mark` for white, as the players choose their colours during the
+/// opening. The number of stones determines the phase: on an empty board the
+/// bot places the first three stones, and after three stones (or five stones
+/// with `swap2`) it makes its choice. Other fields are like in `Board`.
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct OpeningBoard {
+    pub cells: Vec<i8>,
+    pub cells_to_win: u8,
+    pub black_mark: i8,
+    pub white_mark: i8,
+    pub empty_mark: i8,
+    #[serde(default)]
+    pub rows: Option<u8>,
+    #[serde(default)]
+    pub cols: Option<u8>,
+    #[serde(default)]
+    pub win_rule: WinRule,
+    pub opening: OpeningRule,
+}
+
+impl OpeningBoard {
+    pub fn stones_count(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell != self.empty_mark)
+            .count()
+    }
+}
+
+impl From<OpeningBoard> for Board {
+    /// Board on which the bot plays white and black has made the latest move.
+    fn from(board: OpeningBoard) -> Self {
+        // Opening stones are placed freely, so any black stone can be the latest move
+        let last_move = board
+            .cells
+            .iter()
+            .position(|&cell| cell == board.black_mark)
+            .map(|index| index as u16);
+
+        Board {
+            cells: board.cells,
+            cells_to_win: board.cells_to_win,
+            p1_mark: board.black_mark,
+            bot_mark: board.white_mark,
+            empty_mark: board.empty_mark,
+            rows: board.rows,
+            cols: board.cols,
+            win_rule: board.win_rule,
+            last_move,
+            ..Default::default()
+        }
+    }
+}
+
+/// Choice of the bot in the opening phase.
+///
+/// `place_three` and `place_two` mean that the bot placed the opening stones
+/// or the two additional stones of `swap2`, whereas `black` and `white` give
+/// the colour the bot chose to play for the rest of the game.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OpeningChoice {
+    PlaceThree,
+    PlaceTwo,
+    Black,
+    White,
+}
+
+/// Bot's move in the opening phase.
+///
+/// `black` and `white` are the board indices of the stones the bot placed
+/// of each colour, empty if the bot chose its colour.
+
+#[derive(Serialize, Clone, Debug)]
+pub struct OpeningMove {
+    pub choice: OpeningChoice,
+    pub black: Vec<u16>,
+    pub white: Vec<u16>,
 }
@@ -0,0 +1,76 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Server wide search settings, read from environment variables at startup.
+///
+/// `threads` is the number of threads used by one minimax search and
+/// `max_searches` the number of searches allowed to run concurrently.
+#[derive(Clone, Copy, Debug)]
+pub struct SearchConf {
+    pub threads: usize,
+    pub max_searches: usize,
+}
+
+/// Limit for the number of bot searches running concurrently.
+///
+/// A search may start only after acquiring a slot with `try_acquire`.
+/// The slot is released when the returned `SearchSlot` is dropped.
+#[derive(Debug)]
+pub struct SearchSlots {
+    in_use: AtomicUsize,
+    max_slots: usize,
+}
+
+/// Acquired slot of `SearchSlots`, released on drop.
+#[derive(Debug)]
+pub struct SearchSlot {
+    slots: Arc<SearchSlots>,
+}
+
+impl SearchSlots {
+    pub fn new(max_slots: usize) -> Self {
+        SearchSlots {
+            in_use: AtomicUsize::new(0),
+            max_slots,
+        }
+    }
+
+    /// Acquire a free slot, or return None if all slots are in use.
+    pub fn try_acquire(slots: Arc<Self>) -> Option<SearchSlot> {
+        slots
+            .in_use
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |in_use| {
+                (in_use < slots.max_slots).then_some(in_use + 1)
+            })
+            .ok()?;
+
+        Some(SearchSlot { slots })
+    }
+}
+
+impl Drop for SearchSlot {
+    fn drop(&mut self) {
+        self.slots.in_use.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_acquire_and_release() {
+        let slots = Arc::new(SearchSlots::new(2));
+
+        let first = SearchSlots::try_acquire(slots.clone());
+        let second = SearchSlots::try_acquire(slots.clone());
+        assert!(first.is_some() && second.is_some());
+        assert!(SearchSlots::try_acquire(slots.clone()).is_none());
+
+        drop(first);
+        assert!(SearchSlots::try_acquire(slots.clone()).is_some());
+        assert_eq!(slots.in_use.load(Ordering::Acquire), 1);
+    }
+}
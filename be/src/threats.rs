@@ -0,0 +1,271 @@
+use std::time::Instant;
+
+use crate::game::Game;
+
+// Max number of attacking moves in a searched forced win sequence
+const VCF_MAX_DEPTH: usize = 12;
+const VCT_MAX_DEPTH: usize = 3;
+
+// Upper limit of visited positions to keep the response time bounded
+const DEFAULT_MAX_NODES: u32 = 50_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThreatMode {
+    /// Victory by continuous fours, every attacking move threatens an immediate win.
+    Vcf,
+    /// Victory by continuous threats, attacking moves may also be threes.
+    Vct,
+}
+
+/// Threat-space search for forced wins.
+///
+/// Terms four and three are used in gomoku sense for any `cells_to_win`: a four
+/// is a move after which the attacker wins immediately unless the defender blocks
+/// the winning cell, and a three is a move after which the attacker would win by
+/// continuous fours if the defender ignored it. Fours leave the defender a single
+/// reply, for threes only the cells of the attacker's nearly complete windows and
+/// the defender's own fours are considered as replies.
+///
+/// The node limit and the optional deadline are shared by all searches made
+/// with the same instance. After reaching either the searches give up, so a
+/// found win is always a forced one but a missing one doesn't prove there is
+/// none unless the search wasn't exhausted.
+pub struct ThreatSearch {
+    nodes_left: u32,
+    deadline: Option<Instant>,
+}
+
+impl Default for ThreatSearch {
+    fn default() -> Self {
+        ThreatSearch {
+            nodes_left: DEFAULT_MAX_NODES,
+            deadline: None,
+        }
+    }
+}
+
+impl ThreatSearch {
+    pub fn with_deadline(deadline: Option<Instant>) -> Self {
+        ThreatSearch {
+            deadline,
+            ..Default::default()
+        }
+    }
+
+    /// First move of a forced win for `attacker` if it was the attacker's turn.
+    pub fn winning_move(
+        &mut self,
+        game: &mut Game,
+        attacker: i8,
+        mode: ThreatMode,
+    ) -> Option<usize> {
+        let max_depth = match mode {
+            ThreatMode::Vcf => VCF_MAX_DEPTH,
+            ThreatMode::Vct => VCT_MAX_DEPTH,
+        };
+        self.search(game, attacker, mode, max_depth)
+    }
+
+    /// Whether the node limit or the deadline has been reached.
+    pub fn exhausted(&self) -> bool {
+        self.nodes_left == 0
+    }
+
+    fn search(
+        &mut self,
+        game: &mut Game,
+        attacker: i8,
+        mode: ThreatMode,
+        depth: usize,
+    ) -> Option<usize> {
+        if let Some(&win_move) = game.threat_cells(attacker, 1).first() {
+            return Some(win_move);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.nodes_left = 0;
+        }
+        if depth == 0 || self.nodes_left == 0 {
+            return None;
+        }
+        self.nodes_left -= 1;
+
+        let defender = game.opponent(attacker);
+        let defender_wins = game.threat_cells(defender, 1);
+
+        if defender_wins.len() > 1 {
+            return None;
+        }
+
+        let mut candidates = game.threat_cells(attacker, 2);
+
+        if mode == ThreatMode::Vct {
+            let threes: Vec<usize> = game
+                .threat_cells(attacker, 3)
+                .into_iter()
+                .filter(|idx| candidates.binary_search(idx).is_err())
+                .collect();
+            candidates.extend(threes);
+        }
+        if let Some(&defender_win) = defender_wins.first() {
+            // Attacker must block, a forced win continues only if the block is a threat too
+            candidates.retain(|&idx| idx == defender_win);
+        }
+
+        candidates.into_iter().find(|&idx| {
+            game.make_move(idx, attacker);
+            let threat_wins = self.threat_wins(game, attacker, mode, depth);
+            game.undo_move(idx);
+
+            threat_wins
+        })
+    }
+
+    /// Whether the attacker's latest move wins against every defence.
+    fn threat_wins(
+        &mut self,
+        game: &mut Game,
+        attacker: i8,
+        mode: ThreatMode,
+        depth: usize,
+    ) -> bool {
+        let attacker_wins = game.threat_cells(attacker, 1);
+
+        match attacker_wins.len() {
+            0 if mode == ThreatMode::Vct => {
+                if self
+                    .search(game, attacker, ThreatMode::Vcf, VCF_MAX_DEPTH)
+                    .is_none()
+                {
+                    return false;
+                }
+                let defender = game.opponent(attacker);
+
+                let mut defences = game.threat_cells(attacker, 2);
+                defences.extend(game.threat_cells(defender, 2));
+                defences.sort_unstable();
+                defences.dedup();
+
+                defences
+                    .into_iter()
+                    .all(|idx| self.wins_after_defence(game, idx, attacker, mode, depth))
+            }
+            0 => false,
+            1 => self.wins_after_defence(game, attacker_wins[0], attacker, mode, depth),
+            // The defender can block only one of the winning cells
+            _ => true,
+        }
+    }
+
+    fn wins_after_defence(
+        &mut self,
+        game: &mut Game,
+        defence: usize,
+        attacker: i8,
+        mode: ThreatMode,
+        depth: usize,
+    ) -> bool {
+        let defender = game.opponent(attacker);
+
+        game.make_move(defence, defender);
+        let attacker_wins = game.winner_after_move(defence) != defender
+            && self.search(game, attacker, mode, depth - 1).is_some();
+        game.undo_move(defence);
+
+        attacker_wins
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Board, Level};
+
+    const OFFSET: usize = 15;
+    const P1_MARK: i8 = -1;
+    const BOT_MARK: i8 = 1;
+
+    fn init_15x15_game(bot_cells: &[(usize, usize)], p1_cells: &[(usize, usize)]) -> Game {
+        let mut cells = vec![0; OFFSET * OFFSET];
+
+        for &(row, col) in bot_cells.iter() {
+            cells[row * OFFSET + col] = BOT_MARK;
+        }
+        for &(row, col) in p1_cells.iter() {
+            cells[row * OFFSET + col] = P1_MARK;
+        }
+
+        let board = Board {
+            cells,
+            cells_to_win: 5,
+            p1_mark: P1_MARK,
+            bot_mark: BOT_MARK,
+            empty_mark: 0,
+            ..Default::default()
+        };
+        Game::new(board, Level::Normal).unwrap()
+    }
+
+    #[test]
+    fn vcf_with_two_fours_15x15() {
+        // Four in row 7 forces the block at (7, 5), after which column 4 becomes
+        // an open four
+        let mut game = init_15x15_game(
+            &[(7, 1), (7, 2), (7, 3), (8, 4), (9, 4)],
+            &[(7, 0), (0, 0), (0, 14), (14, 0), (14, 14)],
+        );
+
+        let win_move = ThreatSearch::default().winning_move(&mut game, BOT_MARK, ThreatMode::Vcf);
+        assert_eq!(win_move, Some(7 * OFFSET + 4));
+
+        // No forced win for p1
+        let win_move = ThreatSearch::default().winning_move(&mut game, P1_MARK, ThreatMode::Vcf);
+        assert_eq!(win_move, None);
+    }
+
+    #[test]
+    fn vcf_after_deadline_15x15() {
+        let mut game = init_15x15_game(
+            &[(7, 1), (7, 2), (7, 3), (8, 4), (9, 4)],
+            &[(7, 0), (0, 0), (0, 14), (14, 0), (14, 14)],
+        );
+        let mut threat_search = ThreatSearch::with_deadline(Some(Instant::now()));
+
+        let win_move = threat_search.winning_move(&mut game, BOT_MARK, ThreatMode::Vcf);
+        assert_eq!(win_move, None);
+        assert!(threat_search.exhausted());
+    }
+
+    #[test]
+    fn vcf_blocked_by_defender_four_15x15() {
+        // Same as above but p1 has a four of its own which must be blocked first
+        let mut game = init_15x15_game(
+            &[(7, 1), (7, 2), (7, 3), (8, 4), (9, 4)],
+            &[(7, 0), (0, 0), (0, 1), (0, 2), (0, 3)],
+        );
+
+        let win_move = ThreatSearch::default().winning_move(&mut game, BOT_MARK, ThreatMode::Vcf);
+        assert_eq!(win_move, None);
+    }
+
+    #[test]
+    fn vct_with_open_three_15x15() {
+        // Open two in row 7 and a two in column 10 sharing cell (7, 10): no fours
+        // available but the open threes lead to a win
+        let mut game = init_15x15_game(
+            &[(7, 7), (7, 8), (5, 10), (6, 10)],
+            &[(0, 0), (0, 14), (14, 0), (14, 14)],
+        );
+
+        let mut search = ThreatSearch::default();
+        assert_eq!(
+            search.winning_move(&mut game, BOT_MARK, ThreatMode::Vcf),
+            None
+        );
+        assert!(search
+            .winning_move(&mut game, BOT_MARK, ThreatMode::Vct)
+            .is_some());
+    }
+}
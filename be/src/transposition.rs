@@ -0,0 +1,129 @@
+use std::{cmp, collections::HashMap};
+
+use rand::Rng;
+
+// Captured pair counts with distinct keys, enough for any game still going on
+const CAPTURE_KEYS: usize = 16;
+
+/// Random keys for Zobrist hashing of game board positions.
+///
+/// Every cell has one key for each player marker. Hash of a position
+/// is the XOR of the keys of all occupied cells, which makes it cheap
+/// to update the hash incrementally when a marker is placed or removed.
+///
+/// In games with captures, also the captured pair counts of the players
+/// have their own keys.
+#[derive(Clone, Debug)]
+pub struct Zobrist {
+    keys: Vec<[u64; 2]>,
+    capture_keys: [[u64; 2]; CAPTURE_KEYS],
+}
+
+impl Zobrist {
+    pub fn new(cells_count: usize) -> Self {
+        let mut rng = rand::thread_rng();
+
+        Zobrist {
+            keys: (0..cells_count).map(|_| [rng.gen(), rng.gen()]).collect(),
+            capture_keys: std::array::from_fn(|_| [rng.gen(), rng.gen()]),
+        }
+    }
+
+    /// Key of the captured pair counts, indexed like the player bitboards.
+    pub fn captures_key(&self, captured_pairs: [u8; 2]) -> u64 {
+        let key =
+            |p: usize| self.capture_keys[cmp::min(captured_pairs[p] as usize, CAPTURE_KEYS - 1)][p];
+        key(0) ^ key(1)
+    }
+
+    pub fn key(&self, index: usize, mark: i8) -> u64 {
+        // Normalized marks are expected, positive for the bot and negative for p1
+        self.keys[index][(mark > 0) as usize]
+    }
+
+    pub fn hash(&self, cells: &[i8], empty_mark: i8) -> u64 {
+        cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell != empty_mark)
+            .fold(0, |hash, (idx, &cell)| hash ^ self.key(idx, cell))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+/// Stored search result for one position.
+///
+/// Values of the minimax search are scaled by the remaining depth, so
+/// `value` can be reused only for a search of the same `depth`. The
+/// `best_move` is useful as a move ordering hint regardless of the depth.
+#[derive(Clone, Copy, Debug)]
+pub struct TtEntry {
+    pub depth: i32,
+    pub value: i32,
+    pub bound: Bound,
+    pub best_move: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct TranspositionTable {
+    entries: HashMap<u64, TtEntry>,
+}
+
+impl TranspositionTable {
+    pub fn get(&self, hash: u64) -> Option<&TtEntry> {
+        self.entries.get(&hash)
+    }
+
+    pub fn store(&mut self, hash: u64, entry: TtEntry) {
+        match self.entries.get(&hash) {
+            // Keep results of deeper searches
+            Some(old_entry) if old_entry.depth > entry.depth => (),
+            _ => {
+                self.entries.insert(hash, entry);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zobrist_hash_incremental_update() {
+        let zobrist = Zobrist::new(9);
+        let mut cells = vec![0, 0, 1, 0, -1, 0, 0, 0, 0];
+
+        let mut hash = zobrist.hash(&cells, 0);
+
+        cells[6] = 1;
+        hash ^= zobrist.key(6, 1);
+        assert_eq!(hash, zobrist.hash(&cells, 0));
+
+        cells[2] = 0;
+        hash ^= zobrist.key(2, 1);
+        assert_eq!(hash, zobrist.hash(&cells, 0));
+    }
+
+    #[test]
+    fn table_keeps_deeper_entry() {
+        let mut table = TranspositionTable::default();
+        let entry = TtEntry {
+            depth: 3,
+            value: 10,
+            bound: Bound::Exact,
+            best_move: Some(4),
+        };
+        table.store(1, entry);
+        table.store(1, TtEntry { depth: 2, ..entry });
+
+        assert_eq!(table.get(1).unwrap().depth, 3);
+        assert!(table.get(2).is_none());
+    }
+}